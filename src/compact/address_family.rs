@@ -0,0 +1,28 @@
+use std::net::SocketAddr;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum AddressFamily {
+    V4,
+    V6
+}
+
+impl AddressFamily {
+
+    pub fn of(addr: &SocketAddr) -> Self {
+        match addr {
+            SocketAddr::V4(_) => Self::V4,
+            SocketAddr::V6(_) => Self::V6
+        }
+    }
+
+    pub fn address_length(&self) -> usize {
+        match self {
+            Self::V4 => 4,
+            Self::V6 => 16
+        }
+    }
+
+    pub fn peer_length(&self) -> usize {
+        self.address_length() + 2
+    }
+}
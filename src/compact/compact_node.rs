@@ -0,0 +1,89 @@
+use std::io;
+use std::net::SocketAddr;
+use crate::compact::address_family::AddressFamily;
+use crate::compact::compact_peer::{decode_peer, encode_peer};
+use crate::krpc::node_id::{NodeId, ID_LENGTH};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct CompactNode {
+    id: NodeId,
+    address: SocketAddr
+}
+
+impl CompactNode {
+
+    pub fn new(id: NodeId, address: SocketAddr) -> Self {
+        Self {
+            id,
+            address
+        }
+    }
+
+    pub fn get_id(&self) -> &NodeId {
+        &self.id
+    }
+
+    pub fn get_address(&self) -> &SocketAddr {
+        &self.address
+    }
+
+    pub fn get_family(&self) -> AddressFamily {
+        AddressFamily::of(&self.address)
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buf = self.id.as_bytes().to_vec();
+        buf.extend_from_slice(&encode_peer(&self.address));
+        buf
+    }
+
+    pub fn decode(buf: &[u8]) -> io::Result<Self> {
+        if buf.len() != node_length(AddressFamily::V4) && buf.len() != node_length(AddressFamily::V6) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Compact node must be 26 or 38 bytes"));
+        }
+
+        Ok(Self {
+            id: NodeId::from_bytes(&buf[..ID_LENGTH])?,
+            address: decode_peer(&buf[ID_LENGTH..])?
+        })
+    }
+}
+
+pub fn node_length(family: AddressFamily) -> usize {
+    ID_LENGTH + family.peer_length()
+}
+
+pub fn encode_nodes(nodes: &[CompactNode], family: AddressFamily) -> io::Result<Vec<u8>> {
+    let mut buf = Vec::with_capacity(nodes.len() * node_length(family));
+
+    for node in nodes {
+        if node.get_family() != family {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Node address family does not match the list"));
+        }
+        buf.extend_from_slice(&node.encode());
+    }
+
+    Ok(buf)
+}
+
+pub fn decode_nodes(buf: &[u8], family: AddressFamily) -> io::Result<Vec<CompactNode>> {
+    if !buf.len().is_multiple_of(node_length(family)) {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Compact node list length is not a multiple of the record size"));
+    }
+
+    buf.chunks_exact(node_length(family)).map(CompactNode::decode).collect()
+}
+
+pub fn split_nodes(nodes: &[CompactNode]) -> (Vec<u8>, Vec<u8>) {
+    let mut v4 = Vec::new();
+    let mut v6 = Vec::new();
+
+    for node in nodes {
+        match node.get_family() {
+            AddressFamily::V4 => v4.extend_from_slice(&node.encode()),
+            AddressFamily::V6 => v6.extend_from_slice(&node.encode())
+        }
+    }
+
+    (v4, v6)
+}
@@ -0,0 +1,66 @@
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use crate::compact::address_family::AddressFamily;
+
+pub fn encode_peer(addr: &SocketAddr) -> Vec<u8> {
+    let mut buf = match addr.ip() {
+        IpAddr::V4(ip) => ip.octets().to_vec(),
+        IpAddr::V6(ip) => ip.octets().to_vec()
+    };
+    buf.extend_from_slice(&addr.port().to_be_bytes());
+    buf
+}
+
+pub fn decode_peer(buf: &[u8]) -> io::Result<SocketAddr> {
+    let ip = match buf.len() {
+        6 => IpAddr::V4(Ipv4Addr::from(<[u8; 4]>::try_from(&buf[..4]).unwrap())),
+        18 => IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(&buf[..16]).unwrap())),
+        _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "Compact peer must be 6 or 18 bytes"))
+    };
+
+    let port = u16::from_be_bytes([buf[buf.len() - 2], buf[buf.len() - 1]]);
+    Ok(SocketAddr::new(ip, port))
+}
+
+pub fn encode_peers(addrs: &[SocketAddr], family: AddressFamily) -> io::Result<Vec<u8>> {
+    let mut buf = Vec::with_capacity(addrs.len() * family.peer_length());
+
+    for addr in addrs {
+        if AddressFamily::of(addr) != family {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Peer address family does not match the list"));
+        }
+        buf.extend_from_slice(&encode_peer(addr));
+    }
+
+    Ok(buf)
+}
+
+pub fn decode_peers(buf: &[u8], family: AddressFamily) -> io::Result<Vec<SocketAddr>> {
+    if !buf.len().is_multiple_of(family.peer_length()) {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Compact peer list length is not a multiple of the record size"));
+    }
+
+    buf.chunks_exact(family.peer_length()).map(decode_peer).collect()
+}
+
+pub fn split_peers(addrs: &[SocketAddr]) -> (Vec<u8>, Vec<u8>) {
+    let mut v4 = Vec::new();
+    let mut v6 = Vec::new();
+
+    for addr in addrs {
+        match addr {
+            SocketAddr::V4(_) => v4.extend_from_slice(&encode_peer(addr)),
+            SocketAddr::V6(_) => v6.extend_from_slice(&encode_peer(addr))
+        }
+    }
+
+    (v4, v6)
+}
+
+pub fn encode_values(addrs: &[SocketAddr]) -> Vec<Vec<u8>> {
+    addrs.iter().map(encode_peer).collect()
+}
+
+pub fn decode_values(values: &[Vec<u8>]) -> io::Result<Vec<SocketAddr>> {
+    values.iter().map(|v| decode_peer(v)).collect()
+}
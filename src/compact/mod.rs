@@ -0,0 +1,3 @@
+pub mod address_family;
+pub mod compact_peer;
+pub mod compact_node;
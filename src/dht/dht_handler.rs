@@ -0,0 +1,520 @@
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use crate::compact::compact_node::CompactNode;
+use crate::dht::peer_store::PeerStore;
+use crate::dht::routing_table::{RoutingTable, BUCKET_SIZE};
+use crate::krpc::clock::Clock;
+use crate::krpc::krpc_error::{ErrorCode, KrpcError};
+use crate::krpc::message::{Message, MessageBody};
+use crate::krpc::node_id::NodeId;
+use crate::krpc::query::Query;
+use crate::krpc::response::Response;
+use crate::krpc::secure_node_id::{is_exempt_address, is_secure_node_id};
+use crate::krpc::token_manager::TokenManager;
+use crate::krpc::transaction_manager::{TimeoutAction, Transaction, TransactionManager};
+use crate::variables::bencode_bytes::BencodeBytes;
+use crate::variables::bencode_object::{BencodeObject, GetObject};
+use crate::variables::inter::bencode_variable::{FromBencode, ToBencode};
+
+pub const MAX_LOOKUP_QUERIES: usize = 64;
+pub const LOOKUP_ALPHA: usize = 3;
+pub const LOOKUP_RESULT_LIFETIME: Duration = Duration::from_secs(10 * 60);
+pub const EXTERNAL_ADDRESS_QUORUM: usize = 3;
+pub const MAX_EXTERNAL_ADDRESS_VOTES: usize = 32;
+
+pub type Packet = (Vec<u8>, SocketAddr);
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum LookupKind {
+    FindNode,
+    GetPeers
+}
+
+#[derive(Debug, Clone)]
+struct Lookup {
+    kind: LookupKind,
+    candidates: Vec<CompactNode>,
+    queried: HashSet<SocketAddr>,
+    pending: usize,
+    peers: Vec<SocketAddr>,
+    tokens: Vec<(CompactNode, Vec<u8>)>,
+    completed: Option<Duration>
+}
+
+impl Lookup {
+
+    fn new(kind: LookupKind) -> Self {
+        Self {
+            kind,
+            candidates: Vec::new(),
+            queried: HashSet::new(),
+            pending: 0,
+            peers: Vec::new(),
+            tokens: Vec::new(),
+            completed: None
+        }
+    }
+
+    fn add_candidate(&mut self, node: CompactNode, target: &NodeId) {
+        if self.candidates.iter().any(|c| c.get_address() == node.get_address() || c.get_id() == node.get_id()) {
+            return;
+        }
+
+        let position = self.candidates.partition_point(|c| c.get_id().distance(target) < node.get_id().distance(target));
+        self.candidates.insert(position, node);
+        self.candidates.truncate(MAX_LOOKUP_QUERIES);
+    }
+}
+
+pub struct DhtHandler {
+    id: NodeId,
+    clock: Arc<dyn Clock>,
+    routing_table: RoutingTable,
+    peer_store: PeerStore,
+    tokens: TokenManager,
+    transactions: TransactionManager,
+    lookups: HashMap<NodeId, Lookup>,
+    enforce_secure_ids: bool,
+    address_votes: Vec<(IpAddr, SocketAddr)>,
+    external_address: Option<SocketAddr>
+}
+
+impl DhtHandler {
+
+    pub fn new(id: NodeId, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            id,
+            clock: clock.clone(),
+            routing_table: RoutingTable::new(id, clock.clone()),
+            peer_store: PeerStore::new(clock.clone()),
+            tokens: TokenManager::new(clock.clone()),
+            transactions: TransactionManager::new(clock),
+            lookups: HashMap::new(),
+            enforce_secure_ids: false,
+            address_votes: Vec::new(),
+            external_address: None
+        }
+    }
+
+    pub fn with_routing_table(routing_table: RoutingTable, clock: Arc<dyn Clock>) -> Self {
+        let mut handler = Self::new(*routing_table.get_id(), clock);
+        handler.routing_table = routing_table;
+        handler
+    }
+
+    pub fn get_id(&self) -> &NodeId {
+        &self.id
+    }
+
+    pub fn get_routing_table(&self) -> &RoutingTable {
+        &self.routing_table
+    }
+
+    pub fn get_peer_store(&self) -> &PeerStore {
+        &self.peer_store
+    }
+
+    pub fn get_peer_store_mut(&mut self) -> &mut PeerStore {
+        &mut self.peer_store
+    }
+
+    pub fn set_token_manager(&mut self, tokens: TokenManager) {
+        self.tokens = tokens;
+    }
+
+    pub fn get_transactions_mut(&mut self) -> &mut TransactionManager {
+        &mut self.transactions
+    }
+
+    pub fn is_enforcing_secure_ids(&self) -> bool {
+        self.enforce_secure_ids
+    }
+
+    pub fn set_enforce_secure_ids(&mut self, enforce_secure_ids: bool) {
+        self.enforce_secure_ids = enforce_secure_ids;
+    }
+
+    pub fn get_external_address(&self) -> Option<&SocketAddr> {
+        self.external_address.as_ref()
+    }
+
+    pub fn ping(&mut self, address: SocketAddr) -> Vec<Packet> {
+        let mut out = Vec::new();
+        self.send(Query::Ping { id: self.id }, address, None, &mut out);
+        out
+    }
+
+    pub fn bootstrap(&mut self, addresses: &[SocketAddr]) -> Vec<Packet> {
+        let target = self.id;
+        self.start_lookup(target, LookupKind::FindNode, addresses)
+    }
+
+    pub fn find_node(&mut self, target: NodeId) -> Vec<Packet> {
+        self.start_lookup(target, LookupKind::FindNode, &[])
+    }
+
+    pub fn get_peers(&mut self, info_hash: [u8; 20]) -> Vec<Packet> {
+        self.start_lookup(NodeId::from(info_hash), LookupKind::GetPeers, &[])
+    }
+
+    pub fn announce(&mut self, info_hash: [u8; 20], port: u16, implied_port: bool) -> Vec<Packet> {
+        let mut out = Vec::new();
+
+        let target = NodeId::from(info_hash);
+        let mut tokens = match self.lookups.get(&target) {
+            Some(lookup) => lookup.tokens.clone(),
+            None => return out
+        };
+        tokens.sort_by_key(|(node, _)| node.get_id().distance(&target));
+        tokens.truncate(BUCKET_SIZE);
+
+        for (node, token) in tokens {
+            let query = Query::AnnouncePeer {
+                id: self.id,
+                info_hash,
+                port,
+                implied_port,
+                token
+            };
+            self.send(query, *node.get_address(), Some(*node.get_id()), &mut out);
+        }
+
+        out
+    }
+
+    pub fn is_lookup_active(&self, target: &NodeId) -> bool {
+        self.lookups.get(target).map(|lookup| lookup.completed.is_none()).unwrap_or(false)
+    }
+
+    pub fn get_found_peers(&self, info_hash: &[u8; 20]) -> Vec<SocketAddr> {
+        self.lookups.get(&NodeId::from(*info_hash)).map(|lookup| lookup.peers.clone()).unwrap_or_default()
+    }
+
+    pub fn handle_packet(&mut self, buf: &[u8], from: SocketAddr) -> Vec<Packet> {
+        let mut out = Vec::new();
+
+        let message = match Message::from_bencode(buf) {
+            Ok(message) => message,
+            Err(_) => {
+                let ben = match BencodeObject::from_bencode(buf) {
+                    Ok(ben) => ben,
+                    Err(_) => return out
+                };
+
+                let tid = match ben.get::<BencodeBytes>("t") {
+                    Some(tid) => tid.as_bytes(),
+                    None => return out
+                };
+
+                match ben.get::<BencodeBytes>("y").map(|y| y.as_bytes()) {
+                    Some(b"q") => {
+                        let error = Message::error(tid, KrpcError::from(ErrorCode::Protocol));
+                        out.push((error.to_bencode(), from));
+                    }
+                    Some(b"r") | Some(b"e") => {
+                        if let Ok(transaction) = self.transactions.fail(tid, &from) {
+                            self.fail_query(&transaction, &mut out);
+                        }
+                    }
+                    _ => {}
+                }
+                return out;
+            }
+        };
+
+        match message.get_body() {
+            MessageBody::Query(query) => {
+                if let Some(id) = query.get_id() {
+                    if !message.is_read_only() {
+                        self.insert_node(CompactNode::new(id, from), false);
+                    }
+                }
+
+                let mut reply = match self.answer(query, &from) {
+                    Ok(response) => Message::response(message.get_transaction_id(), response),
+                    Err(error) => Message::error(message.get_transaction_id(), error)
+                };
+                reply.set_ip_address(&from);
+                out.push((reply.to_bencode(), from));
+            }
+            MessageBody::Response(response) => {
+                if let Ok(transaction) = self.transactions.receive(&message, &from) {
+                    if let Ok(Some(address)) = message.get_ip_address() {
+                        self.vote_external_address(&from, address);
+                    }
+
+                    self.insert_node(CompactNode::new(*response.get_id(), from), true);
+                    self.on_response(&transaction, response, &mut out);
+                }
+            }
+            MessageBody::Error(_) => {
+                if let Ok(transaction) = self.transactions.receive(&message, &from) {
+                    self.finish_query(&transaction, &mut out);
+                }
+            }
+        }
+
+        out
+    }
+
+    pub fn tick(&mut self) -> Vec<Packet> {
+        let mut out = Vec::new();
+
+        for action in self.transactions.poll_timeouts() {
+            match action {
+                TimeoutAction::Retry(message, address) => out.push((message.to_bencode(), address)),
+                TimeoutAction::Expired(transaction) => self.fail_query(&transaction, &mut out)
+            }
+        }
+
+        let now = self.clock.now();
+        self.lookups.retain(|_, lookup| match lookup.completed {
+            Some(completed) => now.saturating_sub(completed) < LOOKUP_RESULT_LIFETIME,
+            None => true
+        });
+
+        self.peer_store.expire();
+        out
+    }
+
+    fn answer(&mut self, query: &Query, from: &SocketAddr) -> Result<Response, KrpcError> {
+        match query {
+            Query::Ping { .. } => Ok(Response::new(self.id)),
+            Query::FindNode { target, .. } => {
+                let mut response = Response::new(self.id);
+                for node in self.routing_table.find_closest(target, BUCKET_SIZE) {
+                    response.add_node(node);
+                }
+                Ok(response)
+            }
+            Query::GetPeers { info_hash, .. } => {
+                let mut response = Response::new(self.id);
+                response.set_token(&self.tokens.generate(&from.ip()));
+
+                let peers = self.peer_store.get_peers(info_hash);
+                if peers.is_empty() {
+                    for node in self.routing_table.find_closest(&NodeId::from(*info_hash), BUCKET_SIZE) {
+                        response.add_node(node);
+                    }
+                } else {
+                    response.set_values(peers);
+                }
+                Ok(response)
+            }
+            Query::AnnouncePeer { info_hash, port, implied_port, token, .. } => {
+                self.tokens.check(&from.ip(), token)?;
+
+                let port = if *implied_port { from.port() } else { *port };
+                self.peer_store.announce(*info_hash, SocketAddr::new(from.ip(), port));
+                Ok(Response::new(self.id))
+            }
+            _ => Err(KrpcError::from(ErrorCode::MethodUnknown))
+        }
+    }
+
+    fn on_response(&mut self, transaction: &Transaction, response: &Response, out: &mut Vec<Packet>) {
+        let target = match lookup_target(transaction.get_query()) {
+            Some(target) => target,
+            None => return
+        };
+
+        if let Some(lookup) = self.lookups.get_mut(&target) {
+            if lookup.completed.is_none() {
+                let responder = CompactNode::new(*response.get_id(), *transaction.get_address());
+                lookup.pending = lookup.pending.saturating_sub(1);
+                lookup.add_candidate(responder, &target);
+
+                if lookup.kind == LookupKind::GetPeers {
+                    if let Some(token) = response.get_token() {
+                        lookup.tokens.retain(|(n, _)| n.get_address() != transaction.get_address());
+                        lookup.tokens.push((responder, token.clone()));
+                    }
+
+                    if let Some(values) = response.get_values() {
+                        for peer in values {
+                            if !lookup.peers.contains(peer) {
+                                lookup.peers.push(*peer);
+                            }
+                        }
+                    }
+                }
+
+                for node in response.get_all_nodes() {
+                    if *node.get_id() != self.id && !lookup.queried.contains(node.get_address()) {
+                        lookup.add_candidate(*node, &target);
+                    }
+                }
+            }
+        }
+
+        for node in response.get_all_nodes() {
+            self.insert_node(*node, false);
+        }
+
+        self.step_lookup(&target, out);
+    }
+
+    fn vote_external_address(&mut self, from: &SocketAddr, address: SocketAddr) {
+        if is_exempt_address(&from.ip()) || is_exempt_address(&address.ip()) || address.ip().is_unspecified() {
+            return;
+        }
+
+        self.address_votes.retain(|(voter, _)| *voter != from.ip());
+        self.address_votes.push((from.ip(), address));
+        if self.address_votes.len() > MAX_EXTERNAL_ADDRESS_VOTES {
+            self.address_votes.remove(0);
+        }
+
+        let votes = self.address_votes.iter().filter(|(_, a)| *a == address).count();
+        let current = self.external_address
+            .map(|current| self.address_votes.iter().filter(|(_, a)| *a == current).count())
+            .unwrap_or(0);
+
+        if votes >= EXTERNAL_ADDRESS_QUORUM && votes > current {
+            self.external_address = Some(address);
+        }
+    }
+
+    fn insert_node(&mut self, node: CompactNode, responded: bool) {
+        if self.enforce_secure_ids && !is_secure_node_id(node.get_id(), &node.get_address().ip()) {
+            return;
+        }
+
+        match responded {
+            true => self.routing_table.heard_from(node),
+            false => self.routing_table.seen(node)
+        };
+    }
+
+    fn fail_query(&mut self, transaction: &Transaction, out: &mut Vec<Packet>) {
+        if let Some(id) = transaction.get_node_id() {
+            self.routing_table.failed(id);
+        }
+        self.finish_query(transaction, out);
+    }
+
+    fn finish_query(&mut self, transaction: &Transaction, out: &mut Vec<Packet>) {
+        let target = match lookup_target(transaction.get_query()) {
+            Some(target) => target,
+            None => return
+        };
+
+        if let Some(lookup) = self.lookups.get_mut(&target) {
+            if lookup.completed.is_none() {
+                lookup.pending = lookup.pending.saturating_sub(1);
+                lookup.candidates.retain(|c| c.get_address() != transaction.get_address());
+            }
+        }
+
+        self.step_lookup(&target, out);
+    }
+
+    fn start_lookup(&mut self, target: NodeId, kind: LookupKind, addresses: &[SocketAddr]) -> Vec<Packet> {
+        let mut out = Vec::new();
+
+        let active = self.lookups.get(&target).map(|lookup| lookup.completed.is_none() && lookup.kind == kind).unwrap_or(false);
+        if !active {
+            self.lookups.insert(target, Lookup::new(kind));
+        }
+
+        let closest = self.routing_table.find_closest(&target, BUCKET_SIZE);
+        let lookup = self.lookups.get_mut(&target).unwrap();
+        for node in closest {
+            if !lookup.queried.contains(node.get_address()) {
+                lookup.add_candidate(node, &target);
+            }
+        }
+
+        let addresses: Vec<SocketAddr> = addresses.iter()
+            .filter(|a| !lookup.candidates.iter().any(|c| c.get_address() == *a) && lookup.queried.insert(**a))
+            .copied()
+            .collect();
+        lookup.pending += addresses.len();
+
+        for address in addresses {
+            let query = self.lookup_query(&target);
+            if !self.send(query, address, None, &mut out) {
+                self.lookups.get_mut(&target).unwrap().pending -= 1;
+            }
+        }
+
+        self.step_lookup(&target, &mut out);
+        out
+    }
+
+    fn step_lookup(&mut self, target: &NodeId, out: &mut Vec<Packet>) {
+        let next = match self.lookups.get_mut(target) {
+            Some(lookup) if lookup.completed.is_none() => {
+                let mut next = Vec::new();
+                for node in lookup.candidates.iter().take(BUCKET_SIZE) {
+                    if lookup.pending + next.len() >= LOOKUP_ALPHA || lookup.queried.len() + next.len() >= MAX_LOOKUP_QUERIES {
+                        break;
+                    }
+
+                    if !lookup.queried.contains(node.get_address()) {
+                        next.push(*node);
+                    }
+                }
+
+                for node in &next {
+                    lookup.queried.insert(*node.get_address());
+                }
+                lookup.pending += next.len();
+                next
+            }
+            _ => return
+        };
+
+        for node in next {
+            let query = self.lookup_query(target);
+            if !self.send(query, *node.get_address(), Some(*node.get_id()), out) {
+                self.lookups.get_mut(target).unwrap().pending -= 1;
+            }
+        }
+
+        let now = self.clock.now();
+        if let Some(lookup) = self.lookups.get_mut(target) {
+            if lookup.pending == 0 {
+                match lookup.kind {
+                    LookupKind::FindNode => {
+                        self.lookups.remove(target);
+                    }
+                    LookupKind::GetPeers => lookup.completed = Some(now)
+                }
+            }
+        }
+    }
+
+    fn lookup_query(&self, target: &NodeId) -> Query {
+        match self.lookups.get(target).map(|lookup| lookup.kind) {
+            Some(LookupKind::GetPeers) => Query::GetPeers {
+                id: self.id,
+                info_hash: *target.as_bytes()
+            },
+            _ => Query::FindNode {
+                id: self.id,
+                target: *target
+            }
+        }
+    }
+
+    fn send(&mut self, query: Query, address: SocketAddr, node_id: Option<NodeId>, out: &mut Vec<Packet>) -> bool {
+        match self.transactions.send_to(query, address, node_id) {
+            Ok(message) => {
+                out.push((message.to_bencode(), address));
+                true
+            }
+            Err(_) => false
+        }
+    }
+}
+
+fn lookup_target(query: &Query) -> Option<NodeId> {
+    match query {
+        Query::FindNode { target, .. } => Some(*target),
+        Query::GetPeers { info_hash, .. } => Some(NodeId::from(*info_hash)),
+        _ => None
+    }
+}
@@ -0,0 +1,114 @@
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use crate::dht::dht_handler::{DhtHandler, Packet};
+use crate::krpc::clock::SystemClock;
+use crate::krpc::node_id::NodeId;
+
+pub const MAX_PACKET_SIZE: usize = 65535;
+pub const MAX_PACKETS_PER_POLL: usize = 256;
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+pub struct DhtNode {
+    socket: UdpSocket,
+    handler: DhtHandler
+}
+
+impl DhtNode {
+
+    pub fn bind<A: ToSocketAddrs>(address: A, id: NodeId) -> io::Result<Self> {
+        Ok(Self {
+            socket: UdpSocket::bind(address)?,
+            handler: DhtHandler::new(id, Arc::new(SystemClock::new()))
+        })
+    }
+
+    pub fn with_handler<A: ToSocketAddrs>(address: A, handler: DhtHandler) -> io::Result<Self> {
+        Ok(Self {
+            socket: UdpSocket::bind(address)?,
+            handler
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    pub fn get_handler(&self) -> &DhtHandler {
+        &self.handler
+    }
+
+    pub fn get_handler_mut(&mut self) -> &mut DhtHandler {
+        &mut self.handler
+    }
+
+    pub fn ping(&mut self, address: SocketAddr) -> io::Result<()> {
+        let packets = self.handler.ping(address);
+        self.send_all(packets)
+    }
+
+    pub fn bootstrap<A: ToSocketAddrs>(&mut self, addresses: &[A]) -> io::Result<()> {
+        let mut resolved = Vec::new();
+        for address in addresses {
+            resolved.extend(address.to_socket_addrs()?);
+        }
+
+        let packets = self.handler.bootstrap(&resolved);
+        self.send_all(packets)
+    }
+
+    pub fn get_peers(&mut self, info_hash: [u8; 20]) -> io::Result<()> {
+        let packets = self.handler.get_peers(info_hash);
+        self.send_all(packets)
+    }
+
+    pub fn announce(&mut self, info_hash: [u8; 20], port: u16, implied_port: bool) -> io::Result<()> {
+        let packets = self.handler.announce(info_hash, port, implied_port);
+        self.send_all(packets)
+    }
+
+    pub fn poll(&mut self, timeout: Duration) -> io::Result<usize> {
+        let mut buf = [0u8; MAX_PACKET_SIZE];
+        let mut received = 0;
+
+        self.socket.set_read_timeout(Some(timeout.max(Duration::from_millis(1))))?;
+
+        while received < MAX_PACKETS_PER_POLL {
+            let (len, from) = match self.socket.recv_from(&mut buf) {
+                Ok(packet) => packet,
+                Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => break,
+                Err(e) if e.kind() == io::ErrorKind::ConnectionReset => continue,
+                Err(e) => return Err(e)
+            };
+
+            received += 1;
+            let packets = self.handler.handle_packet(&buf[..len], from);
+            self.send_all(packets)?;
+            self.socket.set_read_timeout(Some(Duration::from_millis(1)))?;
+        }
+
+        let packets = self.handler.tick();
+        self.send_all(packets)?;
+        Ok(received)
+    }
+
+    pub fn run(&mut self, running: &AtomicBool) -> io::Result<()> {
+        while running.load(Ordering::Relaxed) {
+            self.poll(DEFAULT_POLL_INTERVAL)?;
+        }
+        Ok(())
+    }
+
+    fn send_all(&self, packets: Vec<Packet>) -> io::Result<()> {
+        for (buf, address) in packets {
+            match self.socket.send_to(&buf, address) {
+                Ok(_) => {}
+                Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::ConnectionReset) => {}
+                Err(e) => return Err(e)
+            }
+        }
+        Ok(())
+    }
+}
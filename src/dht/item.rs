@@ -0,0 +1,190 @@
+use std::io;
+use crate::krpc::node_id::NodeId;
+use crate::utils::sha1::Sha1;
+use crate::variables::bencode_bytes::BencodeBytes;
+use crate::variables::bencode_number::BencodeNumber;
+use crate::variables::inter::bencode_variable::{BencodeVariable, FromBencode, ToBencode};
+
+pub const MAX_VALUE_SIZE: usize = 1000;
+pub const MAX_SALT_SIZE: usize = 64;
+
+pub trait Signer {
+
+    fn get_public_key(&self) -> [u8; 32];
+
+    fn sign(&self, message: &[u8]) -> [u8; 64];
+}
+
+pub trait Verifier {
+
+    fn verify(&self, public_key: &[u8; 32], message: &[u8], signature: &[u8; 64]) -> bool;
+}
+
+pub fn check_value(value: &[u8]) -> io::Result<()> {
+    if value.len() > MAX_VALUE_SIZE {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "Item value exceeds 1000 bytes"));
+    }
+
+    let (variable, off) = Box::<dyn BencodeVariable>::from_bencode_with_offset(value)?;
+    if off != value.len() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "Item value must be a single bencoded value"));
+    }
+
+    if variable.to_bencode() != value {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "Item value does not re-encode to the same bytes"));
+    }
+
+    Ok(())
+}
+
+pub(crate) fn value_variable(value: &[u8]) -> Box<dyn BencodeVariable> {
+    Box::<dyn BencodeVariable>::from_bencode(value).unwrap()
+}
+
+pub fn check_salt(salt: &[u8]) -> io::Result<()> {
+    if salt.len() > MAX_SALT_SIZE {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "Item salt exceeds 64 bytes"));
+    }
+    Ok(())
+}
+
+pub fn signing_buffer(salt: &[u8], seq: i64, value: &[u8]) -> Vec<u8> {
+    let mut buf = Vec::new();
+
+    if !salt.is_empty() {
+        buf.extend(BencodeBytes::from("salt").to_bencode());
+        buf.extend(BencodeBytes::from(salt).to_bencode());
+    }
+
+    buf.extend(BencodeBytes::from("seq").to_bencode());
+    buf.extend(BencodeNumber::from(seq).to_bencode());
+    buf.extend(BencodeBytes::from("v").to_bencode());
+    buf.extend_from_slice(value);
+    buf
+}
+
+pub fn immutable_target(value: &[u8]) -> NodeId {
+    NodeId::new(Sha1::digest(value))
+}
+
+pub fn mutable_target(public_key: &[u8; 32], salt: &[u8]) -> NodeId {
+    let mut sha = Sha1::new();
+    sha.update(public_key);
+    sha.update(salt);
+    NodeId::new(sha.finalize())
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ImmutableItem {
+    value: Vec<u8>
+}
+
+impl ImmutableItem {
+
+    pub fn new(value: &[u8]) -> io::Result<Self> {
+        check_value(value)?;
+        Ok(Self {
+            value: value.to_vec()
+        })
+    }
+
+    pub fn from_variable(value: &dyn BencodeVariable) -> io::Result<Self> {
+        Self::new(&value.to_bencode())
+    }
+
+    pub fn get_value(&self) -> &Vec<u8> {
+        &self.value
+    }
+
+    pub fn get_target(&self) -> NodeId {
+        immutable_target(&self.value)
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MutableItem {
+    public_key: [u8; 32],
+    salt: Vec<u8>,
+    seq: i64,
+    value: Vec<u8>,
+    signature: [u8; 64]
+}
+
+impl MutableItem {
+
+    pub fn new(public_key: [u8; 32], salt: &[u8], seq: i64, value: &[u8], signature: [u8; 64]) -> io::Result<Self> {
+        check_value(value)?;
+        check_salt(salt)?;
+
+        Ok(Self {
+            public_key,
+            salt: salt.to_vec(),
+            seq,
+            value: value.to_vec(),
+            signature
+        })
+    }
+
+    pub fn sign(signer: &dyn Signer, salt: &[u8], seq: i64, value: &[u8]) -> io::Result<Self> {
+        check_value(value)?;
+        check_salt(salt)?;
+
+        let signature = signer.sign(&signing_buffer(salt, seq, value));
+        Self::new(signer.get_public_key(), salt, seq, value, signature)
+    }
+
+    pub fn verify(&self, verifier: &dyn Verifier) -> bool {
+        verifier.verify(&self.public_key, &signing_buffer(&self.salt, self.seq, &self.value), &self.signature)
+    }
+
+    pub fn get_public_key(&self) -> &[u8; 32] {
+        &self.public_key
+    }
+
+    pub fn get_salt(&self) -> &Vec<u8> {
+        &self.salt
+    }
+
+    pub fn get_seq(&self) -> i64 {
+        self.seq
+    }
+
+    pub fn get_value(&self) -> &Vec<u8> {
+        &self.value
+    }
+
+    pub fn get_signature(&self) -> &[u8; 64] {
+        &self.signature
+    }
+
+    pub fn get_target(&self) -> NodeId {
+        mutable_target(&self.public_key, &self.salt)
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Item {
+    Immutable(ImmutableItem),
+    Mutable(MutableItem)
+}
+
+impl Item {
+
+    pub fn get_value(&self) -> &Vec<u8> {
+        match self {
+            Self::Immutable(item) => item.get_value(),
+            Self::Mutable(item) => item.get_value()
+        }
+    }
+
+    pub fn get_target(&self) -> NodeId {
+        match self {
+            Self::Immutable(item) => item.get_target(),
+            Self::Mutable(item) => item.get_target()
+        }
+    }
+
+    pub fn is_mutable(&self) -> bool {
+        matches!(self, Self::Mutable(_))
+    }
+}
@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use crate::dht::item::{Item, Verifier};
+use crate::krpc::clock::Clock;
+use crate::krpc::krpc_error::{ErrorCode, KrpcError};
+use crate::krpc::node_id::NodeId;
+use crate::krpc::response::Response;
+
+pub const DEFAULT_ITEM_LIFETIME: Duration = Duration::from_secs(2 * 60 * 60);
+pub const DEFAULT_MAX_ITEMS: usize = 700;
+
+#[derive(Debug, Clone)]
+struct StoredItem {
+    item: Item,
+    stored: Duration
+}
+
+pub struct ItemStore {
+    items: HashMap<NodeId, StoredItem>,
+    lifetime: Duration,
+    max_items: usize,
+    clock: Arc<dyn Clock>
+}
+
+impl ItemStore {
+
+    pub fn new(clock: Arc<dyn Clock>) -> Self {
+        Self {
+            items: HashMap::new(),
+            lifetime: DEFAULT_ITEM_LIFETIME,
+            max_items: DEFAULT_MAX_ITEMS,
+            clock
+        }
+    }
+
+    pub fn get_lifetime(&self) -> Duration {
+        self.lifetime
+    }
+
+    pub fn set_lifetime(&mut self, lifetime: Duration) {
+        self.lifetime = lifetime;
+    }
+
+    pub fn get_max_items(&self) -> usize {
+        self.max_items
+    }
+
+    pub fn set_max_items(&mut self, max_items: usize) {
+        self.max_items = max_items;
+        while self.items.len() > self.max_items {
+            self.evict_oldest();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn put(&mut self, item: Item, cas: Option<i64>, verifier: &dyn Verifier) -> Result<(), KrpcError> {
+        let target = item.get_target();
+        let now = self.clock.now();
+
+        if let Item::Mutable(mutable) = &item {
+            if !mutable.verify(verifier) {
+                return Err(KrpcError::from(ErrorCode::InvalidSignature));
+            }
+
+            if let Some(Item::Mutable(current)) = self.get(&target) {
+                if cas.is_some_and(|cas| cas != current.get_seq()) {
+                    return Err(KrpcError::from(ErrorCode::CasMismatch));
+                }
+
+                if mutable.get_seq() < current.get_seq() {
+                    return Err(KrpcError::from(ErrorCode::SequenceTooLow));
+                }
+
+                if mutable.get_seq() == current.get_seq() {
+                    if mutable.get_value() != current.get_value() {
+                        return Err(KrpcError::from(ErrorCode::SequenceTooLow));
+                    }
+
+                    if let Some(stored) = self.items.get_mut(&target) {
+                        stored.stored = now;
+                    }
+                    return Ok(());
+                }
+            }
+        }
+
+        if !self.items.contains_key(&target) {
+            self.expire();
+            if self.items.len() >= self.max_items {
+                self.evict_oldest();
+            }
+        }
+
+        if self.max_items > 0 {
+            self.items.insert(target, StoredItem {
+                item,
+                stored: now
+            });
+        }
+
+        Ok(())
+    }
+
+    pub fn get(&self, target: &NodeId) -> Option<&Item> {
+        self.items.get(target)
+            .filter(|s| !self.is_expired(s))
+            .map(|s| &s.item)
+    }
+
+    pub fn fill_response(&self, target: &NodeId, seq: Option<i64>, response: &mut Response) -> bool {
+        let item = match self.get(target) {
+            Some(item) => item,
+            None => return false
+        };
+
+        match item {
+            Item::Mutable(mutable) if seq.is_some_and(|seq| mutable.get_seq() <= seq) => response.set_seq(mutable.get_seq()),
+            _ => response.set_item(item)
+        }
+        true
+    }
+
+    pub fn remove(&mut self, target: &NodeId) -> Option<Item> {
+        self.items.remove(target).map(|s| s.item)
+    }
+
+    pub fn expire(&mut self) -> usize {
+        let before = self.items.len();
+        let now = self.clock.now();
+        let lifetime = self.lifetime;
+        self.items.retain(|_, s| now.saturating_sub(s.stored) < lifetime);
+        before - self.items.len()
+    }
+
+    fn is_expired(&self, stored: &StoredItem) -> bool {
+        self.clock.now().saturating_sub(stored.stored) >= self.lifetime
+    }
+
+    fn evict_oldest(&mut self) {
+        if let Some(target) = self.items.iter().min_by_key(|(_, s)| s.stored).map(|(t, _)| *t) {
+            self.items.remove(&target);
+        }
+    }
+}
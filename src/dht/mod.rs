@@ -0,0 +1,7 @@
+pub mod item;
+pub mod item_store;
+pub mod routing_table;
+pub mod peer_store;
+pub mod dht_handler;
+pub mod dht_node;
+pub mod simulator;
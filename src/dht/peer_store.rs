@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use crate::krpc::clock::Clock;
+
+pub const DEFAULT_PEER_LIFETIME: Duration = Duration::from_secs(30 * 60);
+pub const DEFAULT_MAX_PEERS: usize = 100;
+
+pub struct PeerStore {
+    peers: HashMap<[u8; 20], Vec<(SocketAddr, Duration)>>,
+    lifetime: Duration,
+    max_peers: usize,
+    clock: Arc<dyn Clock>
+}
+
+impl PeerStore {
+
+    pub fn new(clock: Arc<dyn Clock>) -> Self {
+        Self {
+            peers: HashMap::new(),
+            lifetime: DEFAULT_PEER_LIFETIME,
+            max_peers: DEFAULT_MAX_PEERS,
+            clock
+        }
+    }
+
+    pub fn set_lifetime(&mut self, lifetime: Duration) {
+        self.lifetime = lifetime;
+    }
+
+    pub fn set_max_peers(&mut self, max_peers: usize) {
+        self.max_peers = max_peers;
+    }
+
+    pub fn len(&self) -> usize {
+        self.peers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.peers.is_empty()
+    }
+
+    pub fn announce(&mut self, info_hash: [u8; 20], peer: SocketAddr) {
+        let now = self.clock.now();
+        let peers = self.peers.entry(info_hash).or_default();
+        peers.retain(|(p, _)| *p != peer);
+
+        if peers.len() >= self.max_peers {
+            peers.remove(0);
+        }
+        peers.push((peer, now));
+    }
+
+    pub fn get_peers(&self, info_hash: &[u8; 20]) -> Vec<SocketAddr> {
+        let now = self.clock.now();
+        self.peers.get(info_hash)
+            .map(|peers| peers.iter()
+                .filter(|(_, at)| now.saturating_sub(*at) < self.lifetime)
+                .map(|(p, _)| *p)
+                .collect())
+            .unwrap_or_default()
+    }
+
+    pub fn expire(&mut self) {
+        let now = self.clock.now();
+        let lifetime = self.lifetime;
+        for peers in self.peers.values_mut() {
+            peers.retain(|(_, at)| now.saturating_sub(*at) < lifetime);
+        }
+        self.peers.retain(|_, peers| !peers.is_empty());
+    }
+}
@@ -0,0 +1,367 @@
+use std::{fs, io};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use crate::compact::address_family::AddressFamily;
+use crate::compact::compact_node::{decode_nodes, CompactNode};
+use crate::krpc::clock::Clock;
+use crate::krpc::node_id::{NodeId, ID_LENGTH};
+use crate::variables::bencode_bytes::BencodeBytes;
+use crate::variables::bencode_number::BencodeNumber;
+use crate::variables::bencode_object::{BencodeObject, GetObject, PutObject};
+use crate::variables::inter::bencode_variable::{BencodeVariable, FromBencode, ToBencode};
+
+pub const BUCKET_SIZE: usize = 8;
+pub const GOOD_INTERVAL: Duration = Duration::from_secs(15 * 60);
+pub const MAX_FAILURES: u32 = 3;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum NodeState {
+    Good,
+    Questionable,
+    Bad
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum InsertResult {
+    Added,
+    Updated,
+    Replacement,
+    Ignored
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RoutingNode {
+    node: CompactNode,
+    last_seen: Duration,
+    last_response: Option<Duration>,
+    failures: u32
+}
+
+impl RoutingNode {
+
+    pub fn get_node(&self) -> &CompactNode {
+        &self.node
+    }
+
+    pub fn get_id(&self) -> &NodeId {
+        self.node.get_id()
+    }
+
+    pub fn get_last_seen(&self) -> Duration {
+        self.last_seen
+    }
+
+    pub fn get_failures(&self) -> u32 {
+        self.failures
+    }
+
+    pub fn get_state(&self, now: Duration) -> NodeState {
+        if self.failures >= MAX_FAILURES {
+            return NodeState::Bad;
+        }
+
+        match self.last_response {
+            Some(at) if now.saturating_sub(at) < GOOD_INTERVAL => NodeState::Good,
+            _ => NodeState::Questionable
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Bucket {
+    nodes: Vec<RoutingNode>,
+    replacements: Vec<RoutingNode>
+}
+
+pub struct RoutingTable {
+    id: NodeId,
+    buckets: Vec<Bucket>,
+    clock: Arc<dyn Clock>,
+    timestamp: Option<u64>
+}
+
+impl RoutingTable {
+
+    pub fn new(id: NodeId, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            id,
+            buckets: vec![Bucket::default()],
+            clock,
+            timestamp: None
+        }
+    }
+
+    pub fn get_id(&self) -> &NodeId {
+        &self.id
+    }
+
+    pub fn get_timestamp(&self) -> Option<u64> {
+        self.timestamp
+    }
+
+    pub fn get_bucket_count(&self) -> usize {
+        self.buckets.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.buckets.iter().map(|b| b.nodes.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get_replacement_count(&self) -> usize {
+        self.buckets.iter().map(|b| b.replacements.len()).sum()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &RoutingNode> {
+        self.buckets.iter().flat_map(|b| b.nodes.iter())
+    }
+
+    pub fn get_node(&self, id: &NodeId) -> Option<&RoutingNode> {
+        self.buckets[self.bucket_index(id)].nodes.iter().find(|n| n.get_id() == id)
+    }
+
+    fn bucket_index(&self, id: &NodeId) -> usize {
+        self.id.common_prefix(id).min(self.buckets.len() - 1)
+    }
+
+    pub fn heard_from(&mut self, node: CompactNode) -> InsertResult {
+        self.insert(node, true)
+    }
+
+    pub fn seen(&mut self, node: CompactNode) -> InsertResult {
+        self.insert(node, false)
+    }
+
+    fn insert(&mut self, node: CompactNode, responded: bool) -> InsertResult {
+        if *node.get_id() == self.id {
+            return InsertResult::Ignored;
+        }
+
+        let now = self.clock.now();
+
+        loop {
+            let index = self.bucket_index(node.get_id());
+            let splittable = index == self.buckets.len() - 1 && self.buckets.len() < ID_LENGTH * 8;
+            let bucket = &mut self.buckets[index];
+
+            if let Some(existing) = bucket.nodes.iter_mut().find(|n| n.get_id() == node.get_id()) {
+                if existing.node.get_address() != node.get_address()
+                        && (!responded || existing.get_state(now) == NodeState::Good) {
+                    return InsertResult::Ignored;
+                }
+
+                existing.node = node;
+                existing.last_seen = now;
+                if responded {
+                    existing.last_response = Some(now);
+                    existing.failures = 0;
+                }
+                return InsertResult::Updated;
+            }
+
+            let entry = RoutingNode {
+                node,
+                last_seen: now,
+                last_response: responded.then_some(now),
+                failures: 0
+            };
+
+            if bucket.nodes.len() < BUCKET_SIZE {
+                bucket.replacements.retain(|n| n.get_id() != node.get_id());
+                bucket.nodes.push(entry);
+                return InsertResult::Added;
+            }
+
+            if let Some(bad) = bucket.nodes.iter().position(|n| n.get_state(now) == NodeState::Bad) {
+                bucket.nodes[bad] = entry;
+                return InsertResult::Added;
+            }
+
+            if splittable {
+                self.split();
+                continue;
+            }
+
+            bucket.replacements.retain(|n| n.get_id() != node.get_id());
+            if bucket.replacements.len() >= BUCKET_SIZE {
+                bucket.replacements.remove(0);
+            }
+            bucket.replacements.push(entry);
+            return InsertResult::Replacement;
+        }
+    }
+
+    fn split(&mut self) {
+        let depth = self.buckets.len();
+        let last = self.buckets.last_mut().unwrap();
+        let mut next = Bucket::default();
+
+        let id = self.id;
+        let (far, near): (Vec<_>, Vec<_>) = last.nodes.drain(..).partition(|n| id.common_prefix(n.get_id()) < depth);
+        last.nodes = far;
+        next.nodes = near;
+
+        let (far, near): (Vec<_>, Vec<_>) = last.replacements.drain(..).partition(|n| id.common_prefix(n.get_id()) < depth);
+        last.replacements = far;
+        next.replacements = near;
+
+        while last.nodes.len() < BUCKET_SIZE && !last.replacements.is_empty() {
+            let node = last.replacements.pop().unwrap();
+            last.nodes.push(node);
+        }
+
+        self.buckets.push(next);
+    }
+
+    pub fn failed(&mut self, id: &NodeId) {
+        let now = self.clock.now();
+        let index = self.bucket_index(id);
+        let bucket = &mut self.buckets[index];
+
+        let position = match bucket.nodes.iter().position(|n| n.get_id() == id) {
+            Some(position) => position,
+            None => {
+                bucket.replacements.retain(|n| n.get_id() != id);
+                return;
+            }
+        };
+
+        bucket.nodes[position].failures += 1;
+
+        if bucket.nodes[position].get_state(now) == NodeState::Bad {
+            if let Some(replacement) = bucket.replacements.pop() {
+                bucket.nodes[position] = replacement;
+            }
+        }
+    }
+
+    pub fn remove(&mut self, id: &NodeId) -> bool {
+        let index = self.bucket_index(id);
+        let bucket = &mut self.buckets[index];
+        let before = bucket.nodes.len();
+        bucket.nodes.retain(|n| n.get_id() != id);
+
+        if before != bucket.nodes.len() {
+            if let Some(replacement) = bucket.replacements.pop() {
+                bucket.nodes.push(replacement);
+            }
+            return true;
+        }
+        false
+    }
+
+    pub fn find_closest(&self, target: &NodeId, count: usize) -> Vec<CompactNode> {
+        let now = self.clock.now();
+        let mut nodes: Vec<&RoutingNode> = self.iter().filter(|n| n.get_state(now) != NodeState::Bad).collect();
+        nodes.sort_by_key(|n| n.get_id().distance(target));
+        nodes.into_iter().take(count).map(|n| n.node).collect()
+    }
+
+    pub fn get_questionable(&self) -> Vec<CompactNode> {
+        let now = self.clock.now();
+        self.iter().filter(|n| n.get_state(now) == NodeState::Questionable).map(|n| n.node).collect()
+    }
+
+    pub fn to_object(&self) -> BencodeObject {
+        let nodes: Vec<&RoutingNode> = self.iter().collect();
+        let replacements: Vec<&RoutingNode> = self.buckets.iter().flat_map(|b| b.replacements.iter()).collect();
+
+        let mut ben = BencodeObject::new();
+        ben.put("id", *self.id.as_bytes());
+        put_nodes(&mut ben, "nodes", &nodes);
+        put_nodes(&mut ben, "replacements", &replacements);
+        ben.put("timestamp", self.clock.unix_time().as_secs());
+        ben
+    }
+
+    pub fn from_object(ben: &BencodeObject, clock: Arc<dyn Clock>) -> io::Result<Self> {
+        let id = NodeId::from_bytes(ben.get::<BencodeBytes>("id")
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Routing table id not found"))?.as_bytes())?;
+
+        let mut table = Self::new(id, clock);
+
+        table.timestamp = match ben.get::<BencodeNumber>("timestamp") {
+            Some(timestamp) => Some(timestamp.parse::<u64>()?),
+            None => None
+        };
+
+        table.read_nodes(ben, "nodes", false)?;
+        table.read_nodes(ben, "replacements", true)?;
+        Ok(table)
+    }
+
+    fn read_nodes(&mut self, ben: &BencodeObject, key: &str, replacements: bool) -> io::Result<()> {
+        let now = self.clock.now();
+
+        for (key, family) in [(key.to_string(), AddressFamily::V4), (format!("{}6", key), AddressFamily::V6)] {
+            let nodes = match ben.get::<BencodeBytes>(key.as_str()) {
+                Some(nodes) => decode_nodes(nodes.as_bytes(), family)?,
+                None => continue
+            };
+
+            let failures = ben.get::<BencodeBytes>(format!("{}.f", key).as_str()).map(|f| f.as_bytes()).unwrap_or(&[]);
+
+            for (i, node) in nodes.into_iter().enumerate() {
+                self.restore(RoutingNode {
+                    node,
+                    last_seen: now,
+                    last_response: None,
+                    failures: failures.get(i).copied().unwrap_or(0) as u32
+                }, replacements);
+            }
+        }
+        Ok(())
+    }
+
+    fn restore(&mut self, entry: RoutingNode, replacement: bool) {
+        let id = *entry.get_id();
+        if id == self.id || self.get_node(&id).is_some() {
+            return;
+        }
+
+        loop {
+            let index = self.bucket_index(&id);
+            let splittable = index == self.buckets.len() - 1 && self.buckets.len() < ID_LENGTH * 8;
+            let bucket = &mut self.buckets[index];
+
+            if bucket.replacements.iter().any(|n| *n.get_id() == id) {
+                return;
+            }
+
+            if !replacement && bucket.nodes.len() < BUCKET_SIZE {
+                bucket.nodes.push(entry);
+                return;
+            }
+
+            if !replacement && splittable {
+                self.split();
+                continue;
+            }
+
+            if bucket.replacements.len() < BUCKET_SIZE {
+                bucket.replacements.push(entry);
+            }
+            return;
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_object().to_bencode())
+    }
+
+    pub fn load(path: &Path, clock: Arc<dyn Clock>) -> io::Result<Self> {
+        Self::from_object(&BencodeObject::from_bencode(&fs::read(path)?)?, clock)
+    }
+}
+
+fn put_nodes(ben: &mut BencodeObject, key: &str, nodes: &[&RoutingNode]) {
+    for (key, family) in [(key.to_string(), AddressFamily::V4), (format!("{}6", key), AddressFamily::V6)] {
+        let nodes: Vec<&&RoutingNode> = nodes.iter().filter(|n| n.node.get_family() == family).collect();
+        ben.put(key.as_str(), nodes.iter().flat_map(|n| n.node.encode()).collect::<Vec<u8>>());
+        ben.put(format!("{}.f", key), nodes.iter().map(|n| n.failures.min(u8::MAX as u32) as u8).collect::<Vec<u8>>());
+    }
+}
@@ -0,0 +1,322 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use crate::compact::compact_node::CompactNode;
+use crate::compact::compact_peer::encode_peer;
+use crate::dht::dht_handler::{DhtHandler, Packet};
+use crate::krpc::clock::{Clock, ManualClock};
+use crate::krpc::message::{Message, MessageBody};
+use crate::krpc::node_id::NodeId;
+use crate::krpc::query::Query;
+use crate::krpc::response::Response;
+use crate::krpc::token_manager::TokenManager;
+use crate::utils::random::Random;
+use crate::utils::sha1::Sha1;
+use crate::variables::inter::bencode_variable::{FromBencode, ToBencode};
+
+pub const DEFAULT_TICK_INTERVAL: Duration = Duration::from_secs(1);
+pub const DHT_PORT: u16 = 6881;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Behavior {
+    Honest,
+    Silent,
+    Garbage,
+    Liar
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
+struct Envelope {
+    deliver_at: Duration,
+    sequence: u64,
+    from: SocketAddr,
+    to: SocketAddr,
+    buf: Vec<u8>
+}
+
+struct Nat {
+    public: SocketAddr,
+    allowed: HashSet<SocketAddr>
+}
+
+struct SimulatedNode {
+    handler: DhtHandler,
+    address: SocketAddr,
+    behavior: Behavior,
+    nat: Option<Nat>
+}
+
+pub struct NetworkSimulator {
+    clock: Arc<ManualClock>,
+    random: Random,
+    nodes: Vec<SimulatedNode>,
+    addresses: HashMap<SocketAddr, usize>,
+    queue: BinaryHeap<Reverse<Envelope>>,
+    sequence: u64,
+    min_latency: Duration,
+    max_latency: Duration,
+    loss: f64,
+    tick_interval: Duration,
+    next_tick: Duration,
+    sent: u64,
+    delivered: u64,
+    dropped: u64,
+    trace: Sha1
+}
+
+impl NetworkSimulator {
+
+    pub fn new(seed: u64) -> Self {
+        Self {
+            clock: Arc::new(ManualClock::new()),
+            random: Random::new(seed),
+            nodes: Vec::new(),
+            addresses: HashMap::new(),
+            queue: BinaryHeap::new(),
+            sequence: 0,
+            min_latency: Duration::from_millis(20),
+            max_latency: Duration::from_millis(100),
+            loss: 0.0,
+            tick_interval: DEFAULT_TICK_INTERVAL,
+            next_tick: DEFAULT_TICK_INTERVAL,
+            sent: 0,
+            delivered: 0,
+            dropped: 0,
+            trace: Sha1::new()
+        }
+    }
+
+    pub fn get_clock(&self) -> Arc<ManualClock> {
+        self.clock.clone()
+    }
+
+    pub fn now(&self) -> Duration {
+        self.clock.now()
+    }
+
+    pub fn set_latency(&mut self, min: Duration, max: Duration) {
+        self.min_latency = min;
+        self.max_latency = max.max(min);
+    }
+
+    pub fn set_loss(&mut self, loss: f64) {
+        self.loss = loss.clamp(0.0, 1.0);
+    }
+
+    pub fn set_tick_interval(&mut self, tick_interval: Duration) {
+        self.tick_interval = tick_interval;
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn get_sent(&self) -> u64 {
+        self.sent
+    }
+
+    pub fn get_delivered(&self) -> u64 {
+        self.delivered
+    }
+
+    pub fn get_dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    pub fn get_trace(&self) -> [u8; 20] {
+        self.trace.clone().finalize()
+    }
+
+    pub fn add_node(&mut self, behavior: Behavior) -> usize {
+        let index = self.nodes.len();
+        let address = SocketAddr::new(IpAddr::V4(host_address(10, index)), DHT_PORT);
+        self.insert_node(address, behavior, None)
+    }
+
+    pub fn add_nat_node(&mut self) -> usize {
+        let index = self.nodes.len();
+        let address = SocketAddr::new(IpAddr::V4(host_address(192, index)), DHT_PORT);
+        let public = SocketAddr::new(IpAddr::V4(host_address(100, index)), 40000 + (index % 20000) as u16);
+
+        self.insert_node(address, Behavior::Honest, Some(Nat {
+            public,
+            allowed: HashSet::new()
+        }))
+    }
+
+    pub fn get_address(&self, node: usize) -> SocketAddr {
+        let node = &self.nodes[node];
+        node.nat.as_ref().map(|nat| nat.public).unwrap_or(node.address)
+    }
+
+    pub fn get_behavior(&self, node: usize) -> Behavior {
+        self.nodes[node].behavior
+    }
+
+    pub fn get_handler(&self, node: usize) -> &DhtHandler {
+        &self.nodes[node].handler
+    }
+
+    pub fn with_handler<F>(&mut self, node: usize, f: F) where F: FnOnce(&mut DhtHandler) -> Vec<Packet> {
+        let packets = f(&mut self.nodes[node].handler);
+        self.dispatch(node, packets);
+    }
+
+    pub fn bootstrap(&mut self, node: usize, addresses: &[SocketAddr]) {
+        self.with_handler(node, |handler| handler.bootstrap(addresses));
+    }
+
+    pub fn run_for(&mut self, duration: Duration) {
+        let end = self.clock.now() + duration;
+
+        loop {
+            let next_packet = self.queue.peek().map(|Reverse(envelope)| envelope.deliver_at);
+            let next = match next_packet {
+                Some(at) if at < self.next_tick => at,
+                _ => self.next_tick
+            };
+
+            if next > end {
+                break;
+            }
+
+            self.clock.set(next);
+
+            if next_packet == Some(next) {
+                let Reverse(envelope) = self.queue.pop().unwrap();
+                self.deliver(envelope);
+            } else {
+                self.next_tick = next + self.tick_interval;
+                for node in 0..self.nodes.len() {
+                    if self.nodes[node].behavior == Behavior::Honest {
+                        let packets = self.nodes[node].handler.tick();
+                        self.dispatch(node, packets);
+                    }
+                }
+            }
+        }
+
+        self.clock.set(end);
+    }
+
+    fn insert_node(&mut self, address: SocketAddr, behavior: Behavior, nat: Option<Nat>) -> usize {
+        let index = self.nodes.len();
+        let id = NodeId::random_with(&mut self.random);
+        let public = nat.as_ref().map(|nat| nat.public).unwrap_or(address);
+
+        let mut handler = DhtHandler::new(id, self.clock.clone());
+        handler.set_token_manager(TokenManager::with_seed(self.clock.clone(), self.random.next_u64()));
+
+        self.nodes.push(SimulatedNode {
+            handler,
+            address,
+            behavior,
+            nat
+        });
+        self.addresses.insert(public, index);
+        index
+    }
+
+    fn dispatch(&mut self, node: usize, packets: Vec<Packet>) {
+        let from = self.get_address(node);
+
+        for (buf, to) in packets {
+            if let Some(nat) = self.nodes[node].nat.as_mut() {
+                nat.allowed.insert(to);
+            }
+
+            self.sent += 1;
+
+            if self.loss > 0.0 && (self.random.next_u64() as f64) / (u64::MAX as f64) < self.loss {
+                self.dropped += 1;
+                continue;
+            }
+
+            let spread = (self.max_latency - self.min_latency).as_micros() as u64;
+            let latency = self.min_latency + Duration::from_micros(self.random.next_range(spread + 1));
+
+            self.sequence += 1;
+            self.queue.push(Reverse(Envelope {
+                deliver_at: self.clock.now() + latency,
+                sequence: self.sequence,
+                from,
+                to,
+                buf
+            }));
+        }
+    }
+
+    fn deliver(&mut self, envelope: Envelope) {
+        let node = match self.addresses.get(&envelope.to) {
+            Some(node) => *node,
+            None => {
+                self.dropped += 1;
+                return;
+            }
+        };
+
+        if let Some(nat) = self.nodes[node].nat.as_ref() {
+            if !nat.allowed.contains(&envelope.from) {
+                self.dropped += 1;
+                return;
+            }
+        }
+
+        self.delivered += 1;
+        self.trace.update(&encode_peer(&envelope.from));
+        self.trace.update(&encode_peer(&envelope.to));
+        self.trace.update(&(envelope.buf.len() as u32).to_be_bytes());
+        self.trace.update(&envelope.buf);
+
+        let packets = match self.nodes[node].behavior {
+            Behavior::Honest => self.nodes[node].handler.handle_packet(&envelope.buf, envelope.from),
+            Behavior::Silent => Vec::new(),
+            Behavior::Garbage => {
+                let len = 1 + self.random.next_range(64) as usize;
+                let buf = (0..len).map(|_| self.random.next_u64() as u8).collect();
+                vec![(buf, envelope.from)]
+            }
+            Behavior::Liar => self.lie(node, &envelope)
+        };
+
+        self.dispatch(node, packets);
+    }
+
+    fn lie(&mut self, node: usize, envelope: &Envelope) -> Vec<Packet> {
+        let message = match Message::from_bencode(&envelope.buf) {
+            Ok(message) => message,
+            Err(_) => return Vec::new()
+        };
+
+        let query = match message.get_body() {
+            MessageBody::Query(query) => query,
+            _ => return Vec::new()
+        };
+
+        let mut response = Response::new(*self.nodes[node].handler.get_id());
+
+        for _ in 0..8 {
+            let id = NodeId::random_with(&mut self.random);
+            let address = SocketAddr::new(IpAddr::V4(host_address(198, self.random.next_range(1 << 24) as usize)), DHT_PORT);
+            response.add_node(CompactNode::new(id, address));
+        }
+
+        if let Query::GetPeers { .. } = query {
+            let token: Vec<u8> = (0..8).map(|_| self.random.next_u64() as u8).collect();
+            response.set_token(&token);
+        }
+
+        vec![(Message::response(message.get_transaction_id(), response).to_bencode(), envelope.from)]
+    }
+}
+
+fn host_address(network: u8, index: usize) -> Ipv4Addr {
+    let host = index + 1;
+    Ipv4Addr::new(network, (host >> 16) as u8, (host >> 8) as u8, host as u8)
+}
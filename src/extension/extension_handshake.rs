@@ -0,0 +1,281 @@
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use crate::variables::bencode_bytes::BencodeBytes;
+use crate::variables::bencode_number::BencodeNumber;
+use crate::variables::bencode_object::{BencodeObject, GetObject, ObjectOptions, PutObject};
+use crate::variables::inter::bencode_variable::{BencodeVariable, FromBencode, ToBencode};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtensionHandshake {
+    extensions: Vec<(Vec<u8>, u8)>,
+    port: Option<u16>,
+    client: Option<Vec<u8>>,
+    your_ip: Option<IpAddr>,
+    ipv6: Option<Ipv6Addr>,
+    ipv4: Option<Ipv4Addr>,
+    reqq: Option<u32>,
+    metadata_size: Option<u64>,
+    upload_only: Option<bool>,
+    extra: BencodeObject
+}
+
+impl ExtensionHandshake {
+
+    pub fn new() -> Self {
+        Self {
+            extensions: Vec::new(),
+            port: None,
+            client: None,
+            your_ip: None,
+            ipv6: None,
+            ipv4: None,
+            reqq: None,
+            metadata_size: None,
+            upload_only: None,
+            extra: BencodeObject::new()
+        }
+    }
+
+    pub fn get_extensions(&self) -> &Vec<(Vec<u8>, u8)> {
+        &self.extensions
+    }
+
+    pub fn get_extension(&self, name: &str) -> Option<u8> {
+        self.extensions.iter().find(|(n, _)| n.as_slice() == name.as_bytes()).map(|(_, id)| *id)
+    }
+
+    pub fn set_extension(&mut self, name: &str, id: u8) {
+        match self.extensions.iter_mut().find(|(n, _)| n.as_slice() == name.as_bytes()) {
+            Some((_, existing)) => *existing = id,
+            None => self.extensions.push((name.as_bytes().to_vec(), id))
+        }
+    }
+
+    pub fn get_port(&self) -> Option<u16> {
+        self.port
+    }
+
+    pub fn set_port(&mut self, port: u16) {
+        self.port = Some(port);
+    }
+
+    pub fn get_client(&self) -> Option<&str> {
+        self.client.as_ref().and_then(|c| std::str::from_utf8(c).ok())
+    }
+
+    pub fn get_client_bytes(&self) -> Option<&Vec<u8>> {
+        self.client.as_ref()
+    }
+
+    pub fn set_client(&mut self, client: &[u8]) {
+        self.client = Some(client.to_vec());
+    }
+
+    pub fn get_your_ip(&self) -> Option<IpAddr> {
+        self.your_ip
+    }
+
+    pub fn set_your_ip(&mut self, ip: IpAddr) {
+        self.your_ip = Some(ip);
+    }
+
+    pub fn get_ipv6(&self) -> Option<Ipv6Addr> {
+        self.ipv6
+    }
+
+    pub fn set_ipv6(&mut self, ip: Ipv6Addr) {
+        self.ipv6 = Some(ip);
+    }
+
+    pub fn get_ipv4(&self) -> Option<Ipv4Addr> {
+        self.ipv4
+    }
+
+    pub fn set_ipv4(&mut self, ip: Ipv4Addr) {
+        self.ipv4 = Some(ip);
+    }
+
+    pub fn get_reqq(&self) -> Option<u32> {
+        self.reqq
+    }
+
+    pub fn set_reqq(&mut self, reqq: u32) {
+        self.reqq = Some(reqq);
+    }
+
+    pub fn get_metadata_size(&self) -> Option<u64> {
+        self.metadata_size
+    }
+
+    pub fn set_metadata_size(&mut self, metadata_size: u64) {
+        self.metadata_size = Some(metadata_size);
+    }
+
+    pub fn is_upload_only(&self) -> bool {
+        self.upload_only.unwrap_or(false)
+    }
+
+    pub fn set_upload_only(&mut self, upload_only: bool) {
+        self.upload_only = Some(upload_only);
+    }
+
+    pub fn get_extra(&self) -> &BencodeObject {
+        &self.extra
+    }
+
+    pub fn get_extra_mut(&mut self) -> &mut BencodeObject {
+        &mut self.extra
+    }
+}
+
+impl Default for ExtensionHandshake {
+
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn read_ip(buf: &[u8]) -> Option<IpAddr> {
+    match buf.len() {
+        4 => Some(IpAddr::V4(Ipv4Addr::from(<[u8; 4]>::try_from(buf).ok()?))),
+        16 => Some(IpAddr::V6(Ipv6Addr::from(<[u8; 16]>::try_from(buf).ok()?))),
+        _ => None
+    }
+}
+
+fn ip_bytes(ip: &IpAddr) -> Vec<u8> {
+    match ip {
+        IpAddr::V4(ip) => ip.octets().to_vec(),
+        IpAddr::V6(ip) => ip.octets().to_vec()
+    }
+}
+
+impl TryFrom<&BencodeObject> for ExtensionHandshake {
+
+    type Error = io::Error;
+
+    fn try_from(ben: &BencodeObject) -> io::Result<Self> {
+        let mut handshake = Self::new();
+        let mut rejected = BencodeObject::new();
+
+        if let Some(m) = ben.get::<BencodeObject>("m") {
+            for (name, id) in m.iter() {
+                match id.as_any().downcast_ref::<BencodeNumber>().and_then(|id| id.parse::<u8>().ok()) {
+                    Some(id) => handshake.extensions.push((name.as_bytes().to_vec(), id)),
+                    None => rejected.put(name.clone(), id.clone())
+                }
+            }
+        }
+
+        let number = |key: &str| ben.get::<BencodeNumber>(key);
+
+        handshake.port = number("p").and_then(|port| port.parse::<u16>().ok());
+        handshake.client = ben.get::<BencodeBytes>("v").map(|v| v.as_bytes().to_vec());
+        handshake.your_ip = ben.get::<BencodeBytes>("yourip").and_then(|ip| read_ip(ip.as_bytes()));
+
+        handshake.ipv6 = match ben.get::<BencodeBytes>("ipv6").and_then(|ip| read_ip(ip.as_bytes())) {
+            Some(IpAddr::V6(ip)) => Some(ip),
+            _ => None
+        };
+
+        handshake.ipv4 = match ben.get::<BencodeBytes>("ipv4").and_then(|ip| read_ip(ip.as_bytes())) {
+            Some(IpAddr::V4(ip)) => Some(ip),
+            _ => None
+        };
+
+        handshake.reqq = number("reqq").and_then(|reqq| reqq.parse::<u32>().ok());
+        handshake.metadata_size = number("metadata_size").and_then(|size| size.parse::<u64>().ok());
+        handshake.upload_only = number("upload_only").and_then(|upload_only| upload_only.parse::<u8>().ok()).map(|u| u != 0);
+
+        for (key, value) in ben.iter() {
+            let parsed = match key.as_bytes() {
+                b"m" if ben.get::<BencodeObject>("m").is_some() => {
+                    if !rejected.is_empty() {
+                        handshake.extra.put("m", rejected.clone());
+                    }
+                    true
+                }
+                b"p" => handshake.port.is_some(),
+                b"v" => handshake.client.is_some(),
+                b"yourip" => handshake.your_ip.is_some(),
+                b"ipv6" => handshake.ipv6.is_some(),
+                b"ipv4" => handshake.ipv4.is_some(),
+                b"reqq" => handshake.reqq.is_some(),
+                b"metadata_size" => handshake.metadata_size.is_some(),
+                b"upload_only" => handshake.upload_only.is_some(),
+                _ => false
+            };
+
+            if !parsed {
+                handshake.extra.put(key.clone(), value.clone());
+            }
+        }
+
+        Ok(handshake)
+    }
+}
+
+impl From<&ExtensionHandshake> for BencodeObject {
+
+    fn from(handshake: &ExtensionHandshake) -> Self {
+        let mut ben = handshake.extra.clone();
+
+        if !handshake.extensions.is_empty() || !handshake.extra.contains_key("m") {
+            let mut m = handshake.extra.get::<BencodeObject>("m").cloned().unwrap_or_default();
+            for (name, id) in &handshake.extensions {
+                m.put(BencodeBytes::from(name.clone()), *id);
+            }
+            ben.put("m", m);
+        }
+
+        if let Some(port) = handshake.port {
+            ben.put("p", port);
+        }
+
+        if let Some(client) = &handshake.client {
+            ben.put("v", client.clone());
+        }
+
+        if let Some(ip) = &handshake.your_ip {
+            ben.put("yourip", ip_bytes(ip));
+        }
+
+        if let Some(ip) = handshake.ipv6 {
+            ben.put("ipv6", ip.octets());
+        }
+
+        if let Some(ip) = handshake.ipv4 {
+            ben.put("ipv4", ip.octets());
+        }
+
+        if let Some(reqq) = handshake.reqq {
+            ben.put("reqq", reqq);
+        }
+
+        if let Some(size) = handshake.metadata_size {
+            ben.put("metadata_size", size);
+        }
+
+        if let Some(upload_only) = handshake.upload_only {
+            ben.put("upload_only", upload_only as u8);
+        }
+
+        ben.sort_keys();
+        ben
+    }
+}
+
+impl FromBencode for ExtensionHandshake {
+
+    fn from_bencode_with_offset(buf: &[u8]) -> io::Result<(Self, usize)> {
+        let (ben, off) = BencodeObject::from_bencode_with_offset(buf)?;
+        Ok((Self::try_from(&ben)?, off))
+    }
+}
+
+impl ToBencode for ExtensionHandshake {
+
+    fn to_bencode(&self) -> Vec<u8> {
+        BencodeObject::from(self).to_bencode()
+    }
+}
@@ -0,0 +1,82 @@
+use crate::extension::extension_handshake::ExtensionHandshake;
+
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct ExtensionRegistry {
+    local: Vec<(String, u8)>,
+    remote: Vec<(String, u8)>
+}
+
+impl ExtensionRegistry {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, name: &str) -> Option<u8> {
+        if let Some(id) = self.get_local_id(name) {
+            return Some(id);
+        }
+
+        let id = (1..=u8::MAX).find(|id| !self.local.iter().any(|(_, i)| i == id))?;
+        self.local.push((name.to_string(), id));
+        Some(id)
+    }
+
+    pub fn register_with_id(&mut self, name: &str, id: u8) -> bool {
+        if id == 0 || self.local.iter().any(|(n, i)| *i == id && n != name) {
+            return false;
+        }
+
+        self.local.retain(|(n, _)| n != name);
+        self.local.push((name.to_string(), id));
+        true
+    }
+
+    pub fn unregister(&mut self, name: &str) -> bool {
+        let before = self.local.len();
+        self.local.retain(|(n, _)| n != name);
+        before != self.local.len()
+    }
+
+    pub fn get_local_id(&self, name: &str) -> Option<u8> {
+        self.local.iter().find(|(n, _)| n == name).map(|(_, id)| *id)
+    }
+
+    pub fn get_local_name(&self, id: u8) -> Option<&String> {
+        self.local.iter().find(|(_, i)| *i == id).map(|(n, _)| n)
+    }
+
+    pub fn get_remote_id(&self, name: &str) -> Option<u8> {
+        self.remote.iter().find(|(n, _)| n == name).map(|(_, id)| *id)
+    }
+
+    pub fn get_remote_name(&self, id: u8) -> Option<&String> {
+        self.remote.iter().find(|(_, i)| *i == id).map(|(n, _)| n)
+    }
+
+    pub fn supports(&self, name: &str) -> bool {
+        self.get_local_id(name).is_some() && self.get_remote_id(name).is_some()
+    }
+
+    pub fn update_remote(&mut self, handshake: &ExtensionHandshake) {
+        for (name, id) in handshake.get_extensions() {
+            let name = match std::str::from_utf8(name) {
+                Ok(name) => name,
+                Err(_) => continue
+            };
+
+            self.remote.retain(|(n, _)| n != name);
+            if *id != 0 {
+                self.remote.push((name.to_string(), *id));
+            }
+        }
+    }
+
+    pub fn to_handshake(&self) -> ExtensionHandshake {
+        let mut handshake = ExtensionHandshake::new();
+        for (name, id) in &self.local {
+            handshake.set_extension(name, *id);
+        }
+        handshake
+    }
+}
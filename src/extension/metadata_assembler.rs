@@ -0,0 +1,187 @@
+use std::io;
+use crate::extension::metadata_message::{piece_count, piece_size, MetadataMessage, METADATA_PIECE_SIZE};
+use crate::magnet::magnet_uri::MagnetUri;
+use crate::metainfo::announce_list::AnnounceList;
+use crate::metainfo::info_hash::InfoHash;
+use crate::metainfo::torrent::Torrent;
+use crate::metainfo::web_seeds::WebSeeds;
+use crate::utils::sha1::Sha1;
+use crate::utils::sha256::Sha256;
+use crate::variables::bencode_array::BencodeArray;
+use crate::variables::bencode_object::{BencodeObject, PutObject};
+use crate::variables::inter::bencode_variable::FromBencode;
+
+pub const MAX_METADATA_SIZE: u64 = 16 * 1024 * 1024;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PieceState {
+    Missing,
+    Requested,
+    Received
+}
+
+#[derive(Debug, Clone)]
+pub struct MetadataAssembler {
+    info_hash: InfoHash,
+    total_size: Option<u64>,
+    pieces: Vec<PieceState>,
+    buffer: Vec<u8>,
+    trackers: Vec<String>,
+    web_seeds: Vec<String>
+}
+
+impl MetadataAssembler {
+
+    pub fn new(info_hash: InfoHash) -> Self {
+        Self {
+            info_hash,
+            total_size: None,
+            pieces: Vec::new(),
+            buffer: Vec::new(),
+            trackers: Vec::new(),
+            web_seeds: Vec::new()
+        }
+    }
+
+    pub fn from_magnet(magnet: &MagnetUri) -> io::Result<Self> {
+        let info_hash = magnet.get_info_hash()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Magnet URI has no info hash"))?;
+
+        let mut assembler = Self::new(*info_hash);
+        assembler.trackers = magnet.get_trackers().clone();
+        assembler.web_seeds = magnet.get_web_seeds().clone();
+        Ok(assembler)
+    }
+
+    pub fn get_info_hash(&self) -> &InfoHash {
+        &self.info_hash
+    }
+
+    pub fn get_total_size(&self) -> Option<u64> {
+        self.total_size
+    }
+
+    pub fn set_total_size(&mut self, total_size: u64) -> io::Result<()> {
+        match self.total_size {
+            Some(size) if size != total_size => {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "Metadata total_size does not match"));
+            }
+            Some(_) => return Ok(()),
+            None => {}
+        }
+
+        if total_size == 0 || total_size > MAX_METADATA_SIZE {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Metadata total_size is out of range"));
+        }
+
+        self.total_size = Some(total_size);
+        self.pieces = vec![PieceState::Missing; piece_count(total_size) as usize];
+        self.buffer = vec![0; total_size as usize];
+        Ok(())
+    }
+
+    pub fn get_piece_count(&self) -> usize {
+        self.pieces.len()
+    }
+
+    pub fn get_piece_state(&self, piece: u32) -> Option<PieceState> {
+        self.pieces.get(piece as usize).copied()
+    }
+
+    pub fn get_received_count(&self) -> usize {
+        self.pieces.iter().filter(|p| **p == PieceState::Received).count()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        !self.pieces.is_empty() && self.pieces.iter().all(|p| *p == PieceState::Received)
+    }
+
+    pub fn next_request(&mut self) -> Option<MetadataMessage> {
+        let piece = self.pieces.iter().position(|p| *p == PieceState::Missing)?;
+        self.pieces[piece] = PieceState::Requested;
+
+        Some(MetadataMessage::Request {
+            piece: piece as u32
+        })
+    }
+
+    pub fn cancel_request(&mut self, piece: u32) {
+        if let Some(state) = self.pieces.get_mut(piece as usize) {
+            if *state == PieceState::Requested {
+                *state = PieceState::Missing;
+            }
+        }
+    }
+
+    pub fn receive(&mut self, message: &MetadataMessage) -> io::Result<bool> {
+        match message {
+            MetadataMessage::Data { piece, total_size, payload } => {
+                self.set_total_size(*total_size)?;
+
+                let size = piece_size(*piece, *total_size)
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Metadata piece is out of range"))?;
+
+                if payload.len() != size {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, "Metadata piece has the wrong size"));
+                }
+
+                let offset = *piece as usize * METADATA_PIECE_SIZE;
+                self.buffer[offset..offset + size].copy_from_slice(payload);
+                self.pieces[*piece as usize] = PieceState::Received;
+            }
+            MetadataMessage::Reject { piece } => self.cancel_request(*piece),
+            MetadataMessage::Request { .. } => {}
+        }
+
+        Ok(self.is_complete())
+    }
+
+    pub fn verify(&mut self) -> io::Result<BencodeObject> {
+        if !self.is_complete() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Metadata is incomplete"));
+        }
+
+        let matches = match &self.info_hash {
+            InfoHash::V1(hash) => Sha1::digest(&self.buffer) == *hash,
+            InfoHash::V2(hash) => Sha256::digest(&self.buffer) == *hash
+        };
+
+        let info = match matches {
+            true => BencodeObject::from_bencode_with_offset(&self.buffer).ok().filter(|(_, off)| *off == self.buffer.len()),
+            false => None
+        };
+
+        match info {
+            Some((info, _)) => Ok(info),
+            None => {
+                self.pieces.iter_mut().for_each(|p| *p = PieceState::Missing);
+                Err(io::Error::new(io::ErrorKind::InvalidData, "Metadata does not match the info hash"))
+            }
+        }
+    }
+
+    pub fn to_torrent(&mut self) -> io::Result<Torrent> {
+        let info = self.verify()?;
+        let mut ben = BencodeObject::new();
+
+        if let Some(tracker) = self.trackers.first() {
+            ben.put("announce", tracker.as_str());
+        }
+
+        if self.trackers.len() > 1 {
+            let mut list = AnnounceList::from_tiers(self.trackers.iter().map(|t| vec![t.clone()]).collect());
+            list.dedup();
+            ben.put("announce-list", BencodeArray::from(&list));
+        }
+
+        ben.put("info", info);
+
+        let mut web_seeds = WebSeeds::new();
+        for seed in &self.web_seeds {
+            let _ = web_seeds.add(seed);
+        }
+        web_seeds.put_into(&mut ben, "url-list");
+
+        Torrent::try_from(&ben)
+    }
+}
@@ -0,0 +1,121 @@
+use std::io;
+use crate::variables::bencode_number::BencodeNumber;
+use crate::variables::bencode_object::{BencodeObject, GetObject, PutObject};
+use crate::variables::inter::bencode_variable::{BencodeVariable, FromBencode, ToBencode};
+
+pub const METADATA_PIECE_SIZE: usize = 16384;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum MetadataMessage {
+    Request {
+        piece: u32
+    },
+    Data {
+        piece: u32,
+        total_size: u64,
+        payload: Vec<u8>
+    },
+    Reject {
+        piece: u32
+    }
+}
+
+impl MetadataMessage {
+
+    pub fn get_msg_type(&self) -> u8 {
+        match self {
+            Self::Request { .. } => 0,
+            Self::Data { .. } => 1,
+            Self::Reject { .. } => 2
+        }
+    }
+
+    pub fn get_piece(&self) -> u32 {
+        match self {
+            Self::Request { piece } |
+            Self::Data { piece, .. } |
+            Self::Reject { piece } => *piece
+        }
+    }
+}
+
+pub fn piece_count(total_size: u64) -> u32 {
+    total_size.div_ceil(METADATA_PIECE_SIZE as u64) as u32
+}
+
+pub fn piece_size(piece: u32, total_size: u64) -> Option<usize> {
+    let start = piece as u64 * METADATA_PIECE_SIZE as u64;
+    if start >= total_size {
+        return None;
+    }
+
+    Some((total_size - start).min(METADATA_PIECE_SIZE as u64) as usize)
+}
+
+pub fn split_payload(buf: &[u8]) -> io::Result<(BencodeObject, &[u8])> {
+    let (ben, off) = BencodeObject::from_bencode_with_offset(buf)?;
+    Ok((ben, &buf[off..]))
+}
+
+impl FromBencode for MetadataMessage {
+
+    fn from_bencode_with_offset(buf: &[u8]) -> io::Result<(Self, usize)> {
+        let (ben, payload) = split_payload(buf)?;
+
+        let number = |key: &str| ben.get::<BencodeNumber>(key)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("ut_metadata {} not found", key)));
+
+        let piece = number("piece")?.parse::<u32>()?;
+
+        let message = match number("msg_type")?.parse::<u8>()? {
+            0 => Self::Request {
+                piece
+            },
+            1 => {
+                let total_size = number("total_size")?.parse::<u64>()?;
+
+                let expected = piece_size(piece, total_size)
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "ut_metadata piece is out of range"))?;
+
+                if payload.len() != expected {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, "ut_metadata payload has the wrong size"));
+                }
+
+                Self::Data {
+                    piece,
+                    total_size,
+                    payload: payload.to_vec()
+                }
+            }
+            2 => Self::Reject {
+                piece
+            },
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "Unknown ut_metadata msg_type"))
+        };
+
+        if !matches!(message, Self::Data { .. }) && !payload.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Unexpected ut_metadata payload"));
+        }
+
+        Ok((message, buf.len()))
+    }
+}
+
+impl ToBencode for MetadataMessage {
+
+    fn to_bencode(&self) -> Vec<u8> {
+        let mut ben = BencodeObject::new();
+        ben.put("msg_type", self.get_msg_type());
+        ben.put("piece", self.get_piece());
+
+        if let Self::Data { total_size, .. } = self {
+            ben.put("total_size", *total_size);
+        }
+
+        let mut buf = ben.to_bencode();
+        if let Self::Data { payload, .. } = self {
+            buf.extend_from_slice(payload);
+        }
+        buf
+    }
+}
@@ -0,0 +1,6 @@
+pub mod extension_handshake;
+pub mod extension_registry;
+pub mod metadata_message;
+pub mod metadata_assembler;
+pub mod pex_flags;
+pub mod pex_message;
@@ -0,0 +1,76 @@
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct PexFlags {
+    encryption: bool,
+    seed: bool,
+    utp: bool,
+    holepunch: bool,
+    outgoing: bool
+}
+
+impl PexFlags {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_encryption(&self) -> bool {
+        self.encryption
+    }
+
+    pub fn is_seed(&self) -> bool {
+        self.seed
+    }
+
+    pub fn is_utp(&self) -> bool {
+        self.utp
+    }
+
+    pub fn is_holepunch(&self) -> bool {
+        self.holepunch
+    }
+
+    pub fn is_outgoing(&self) -> bool {
+        self.outgoing
+    }
+
+    pub fn set_encryption(&mut self, encryption: bool) {
+        self.encryption = encryption;
+    }
+
+    pub fn set_seed(&mut self, seed: bool) {
+        self.seed = seed;
+    }
+
+    pub fn set_utp(&mut self, utp: bool) {
+        self.utp = utp;
+    }
+
+    pub fn set_holepunch(&mut self, holepunch: bool) {
+        self.holepunch = holepunch;
+    }
+
+    pub fn set_outgoing(&mut self, outgoing: bool) {
+        self.outgoing = outgoing;
+    }
+
+    pub fn to_byte(&self) -> u8 {
+        (self.encryption as u8) |
+            (self.seed as u8) << 1 |
+            (self.utp as u8) << 2 |
+            (self.holepunch as u8) << 3 |
+            (self.outgoing as u8) << 4
+    }
+}
+
+impl From<u8> for PexFlags {
+
+    fn from(b: u8) -> Self {
+        Self {
+            encryption: b & 0x01 != 0,
+            seed: b & 0x02 != 0,
+            utp: b & 0x04 != 0,
+            holepunch: b & 0x08 != 0,
+            outgoing: b & 0x10 != 0
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use std::io;
+use std::net::SocketAddr;
+use crate::compact::address_family::AddressFamily;
+use crate::compact::compact_peer::{decode_peers, encode_peer};
+use crate::extension::pex_flags::PexFlags;
+use crate::variables::bencode_bytes::BencodeBytes;
+use crate::variables::bencode_object::{BencodeObject, GetObject, PutObject};
+use crate::variables::inter::bencode_variable::{FromBencode, ToBencode};
+
+pub const MAX_PEX_PEERS: usize = 50;
+
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct PexMessage {
+    added: Vec<(SocketAddr, PexFlags)>,
+    dropped: Vec<SocketAddr>
+}
+
+impl PexMessage {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn diff(previous: &[(SocketAddr, PexFlags)], current: &[(SocketAddr, PexFlags)]) -> Vec<Self> {
+        let added: Vec<(SocketAddr, PexFlags)> = current.iter()
+            .filter(|(addr, _)| !previous.iter().any(|(p, _)| p == addr))
+            .copied()
+            .collect();
+
+        let dropped: Vec<SocketAddr> = previous.iter()
+            .filter(|(addr, _)| !current.iter().any(|(c, _)| c == addr))
+            .map(|(addr, _)| *addr)
+            .collect();
+
+        let count = added.len().max(dropped.len()).div_ceil(MAX_PEX_PEERS);
+        let mut added = added.chunks(MAX_PEX_PEERS);
+        let mut dropped = dropped.chunks(MAX_PEX_PEERS);
+
+        (0..count).map(|_| Self {
+            added: added.next().map(|a| a.to_vec()).unwrap_or_default(),
+            dropped: dropped.next().map(|d| d.to_vec()).unwrap_or_default()
+        }).collect()
+    }
+
+    pub fn get_added(&self) -> &Vec<(SocketAddr, PexFlags)> {
+        &self.added
+    }
+
+    pub fn add_added(&mut self, addr: SocketAddr, flags: PexFlags) -> bool {
+        if self.added.len() >= MAX_PEX_PEERS || self.added.iter().any(|(a, _)| *a == addr) {
+            return false;
+        }
+
+        self.added.push((addr, flags));
+        true
+    }
+
+    pub fn get_dropped(&self) -> &Vec<SocketAddr> {
+        &self.dropped
+    }
+
+    pub fn add_dropped(&mut self, addr: SocketAddr) -> bool {
+        if self.dropped.len() >= MAX_PEX_PEERS || self.dropped.contains(&addr) {
+            return false;
+        }
+
+        self.dropped.push(addr);
+        true
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.dropped.is_empty()
+    }
+}
+
+fn read_added(ben: &BencodeObject, key: &str, family: AddressFamily) -> io::Result<Vec<(SocketAddr, PexFlags)>> {
+    let peers = match ben.get::<BencodeBytes>(key) {
+        Some(peers) => decode_peers(peers.as_bytes(), family)?,
+        None => return Ok(Vec::new())
+    };
+
+    let flags = ben.get::<BencodeBytes>(format!("{}.f", key).as_str()).map(|f| f.as_bytes()).unwrap_or(&[]);
+    if !flags.is_empty() && flags.len() != peers.len() {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "PEX flag count does not match peer count"));
+    }
+
+    Ok(peers.into_iter().enumerate().map(|(i, addr)| {
+        (addr, flags.get(i).map(|f| PexFlags::from(*f)).unwrap_or_default())
+    }).collect())
+}
+
+fn read_dropped(ben: &BencodeObject, key: &str, family: AddressFamily) -> io::Result<Vec<SocketAddr>> {
+    match ben.get::<BencodeBytes>(key) {
+        Some(peers) => decode_peers(peers.as_bytes(), family),
+        None => Ok(Vec::new())
+    }
+}
+
+impl TryFrom<&BencodeObject> for PexMessage {
+
+    type Error = io::Error;
+
+    fn try_from(ben: &BencodeObject) -> io::Result<Self> {
+        let mut added = read_added(ben, "added", AddressFamily::V4)?;
+        added.extend(read_added(ben, "added6", AddressFamily::V6)?);
+
+        let mut dropped = read_dropped(ben, "dropped", AddressFamily::V4)?;
+        dropped.extend(read_dropped(ben, "dropped6", AddressFamily::V6)?);
+
+        added.truncate(MAX_PEX_PEERS);
+        dropped.truncate(MAX_PEX_PEERS);
+
+        Ok(Self {
+            added,
+            dropped
+        })
+    }
+}
+
+impl From<&PexMessage> for BencodeObject {
+
+    fn from(message: &PexMessage) -> Self {
+        let mut ben = BencodeObject::new();
+
+        for (key, family) in [("added", AddressFamily::V4), ("added6", AddressFamily::V6)] {
+            let mut peers = Vec::new();
+            let mut flags = Vec::new();
+            for (addr, f) in message.added.iter().filter(|(a, _)| AddressFamily::of(a) == family) {
+                peers.extend(encode_peer(addr));
+                flags.push(f.to_byte());
+            }
+            ben.put(key, peers);
+            ben.put(format!("{}.f", key), flags);
+        }
+
+        for (key, family) in [("dropped", AddressFamily::V4), ("dropped6", AddressFamily::V6)] {
+            let peers: Vec<u8> = message.dropped.iter()
+                .filter(|a| AddressFamily::of(a) == family)
+                .flat_map(encode_peer)
+                .collect();
+            ben.put(key, peers);
+        }
+
+        ben
+    }
+}
+
+impl FromBencode for PexMessage {
+
+    fn from_bencode_with_offset(buf: &[u8]) -> io::Result<(Self, usize)> {
+        let (ben, off) = BencodeObject::from_bencode_with_offset(buf)?;
+        Ok((Self::try_from(&ben)?, off))
+    }
+}
+
+impl ToBencode for PexMessage {
+
+    fn to_bencode(&self) -> Vec<u8> {
+        BencodeObject::from(self).to_bencode()
+    }
+}
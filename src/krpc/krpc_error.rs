@@ -0,0 +1,128 @@
+use std::io;
+use crate::variables::bencode_array::{AddArray, BencodeArray};
+use crate::variables::bencode_bytes::BencodeBytes;
+use crate::variables::bencode_number::BencodeNumber;
+use crate::variables::inter::bencode_variable::BencodeVariable;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ErrorCode {
+    Generic,
+    Server,
+    Protocol,
+    MethodUnknown,
+    ValueTooBig,
+    InvalidSignature,
+    SaltTooBig,
+    CasMismatch,
+    SequenceTooLow,
+    Other(i64)
+}
+
+impl ErrorCode {
+
+    pub fn from_code(code: i64) -> Self {
+        match code {
+            201 => Self::Generic,
+            202 => Self::Server,
+            203 => Self::Protocol,
+            204 => Self::MethodUnknown,
+            205 => Self::ValueTooBig,
+            206 => Self::InvalidSignature,
+            207 => Self::SaltTooBig,
+            301 => Self::CasMismatch,
+            302 => Self::SequenceTooLow,
+            _ => Self::Other(code)
+        }
+    }
+
+    pub fn code(&self) -> i64 {
+        match self {
+            Self::Generic => 201,
+            Self::Server => 202,
+            Self::Protocol => 203,
+            Self::MethodUnknown => 204,
+            Self::ValueTooBig => 205,
+            Self::InvalidSignature => 206,
+            Self::SaltTooBig => 207,
+            Self::CasMismatch => 301,
+            Self::SequenceTooLow => 302,
+            Self::Other(code) => *code
+        }
+    }
+
+    pub fn default_message(&self) -> &'static str {
+        match self {
+            Self::Generic => "Generic Error",
+            Self::Server => "Server Error",
+            Self::Protocol => "Protocol Error",
+            Self::MethodUnknown => "Method Unknown",
+            Self::ValueTooBig => "Message (v field) too big",
+            Self::InvalidSignature => "Invalid signature",
+            Self::SaltTooBig => "Salt (salt field) too big",
+            Self::CasMismatch => "The CAS hash mismatched, re-read value and try again",
+            Self::SequenceTooLow => "Sequence number less than current",
+            Self::Other(_) => "Error"
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct KrpcError {
+    code: ErrorCode,
+    message: String
+}
+
+impl KrpcError {
+
+    pub fn new(code: ErrorCode, message: &str) -> Self {
+        Self {
+            code,
+            message: message.to_string()
+        }
+    }
+
+    pub fn get_code(&self) -> ErrorCode {
+        self.code
+    }
+
+    pub fn get_message(&self) -> &String {
+        &self.message
+    }
+}
+
+impl From<ErrorCode> for KrpcError {
+
+    fn from(code: ErrorCode) -> Self {
+        Self::new(code, code.default_message())
+    }
+}
+
+impl TryFrom<&BencodeArray> for KrpcError {
+
+    type Error = io::Error;
+
+    fn try_from(ben: &BencodeArray) -> io::Result<Self> {
+        let code = ben.get::<BencodeNumber>(0)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Error code not found"))?
+            .parse::<i64>()?;
+
+        let message = ben.get::<BencodeBytes>(1)
+            .map(|m| String::from_utf8_lossy(m.as_bytes()).into_owned())
+            .unwrap_or_default();
+
+        Ok(Self {
+            code: ErrorCode::from_code(code),
+            message
+        })
+    }
+}
+
+impl From<&KrpcError> for BencodeArray {
+
+    fn from(error: &KrpcError) -> Self {
+        let mut ben = BencodeArray::new();
+        ben.push(error.code.code());
+        ben.push(error.message.as_str());
+        ben
+    }
+}
@@ -0,0 +1,258 @@
+use std::io;
+use std::net::SocketAddr;
+use crate::compact::compact_peer::{decode_peer, encode_peer};
+use crate::krpc::krpc_error::KrpcError;
+use crate::krpc::query::Query;
+use crate::krpc::response::Response;
+use crate::variables::bencode_array::BencodeArray;
+use crate::variables::bencode_bytes::BencodeBytes;
+use crate::variables::bencode_number::BencodeNumber;
+use crate::variables::bencode_object::{BencodeObject, GetObject, PutObject};
+use crate::variables::inter::bencode_variable::{BencodeVariable, FromBencode, ToBencode};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum MessageType {
+    Query,
+    Response,
+    Error
+}
+
+impl MessageType {
+
+    pub fn from_key(key: &[u8]) -> Option<Self> {
+        match key {
+            b"q" => Some(Self::Query),
+            b"r" => Some(Self::Response),
+            b"e" => Some(Self::Error),
+            _ => None
+        }
+    }
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            Self::Query => "q",
+            Self::Response => "r",
+            Self::Error => "e"
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MessageBody {
+    Query(Query),
+    Response(Response),
+    Error(KrpcError)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    transaction_id: Vec<u8>,
+    version: Option<Vec<u8>>,
+    ip: Option<Vec<u8>>,
+    read_only: bool,
+    body: MessageBody
+}
+
+impl Message {
+
+    pub fn new(transaction_id: &[u8], body: MessageBody) -> Self {
+        Self {
+            transaction_id: transaction_id.to_vec(),
+            version: None,
+            ip: None,
+            read_only: false,
+            body
+        }
+    }
+
+    pub fn query(transaction_id: &[u8], query: Query) -> Self {
+        Self::new(transaction_id, MessageBody::Query(query))
+    }
+
+    pub fn response(transaction_id: &[u8], response: Response) -> Self {
+        Self::new(transaction_id, MessageBody::Response(response))
+    }
+
+    pub fn error(transaction_id: &[u8], error: KrpcError) -> Self {
+        Self::new(transaction_id, MessageBody::Error(error))
+    }
+
+    pub fn get_type(&self) -> MessageType {
+        match self.body {
+            MessageBody::Query(_) => MessageType::Query,
+            MessageBody::Response(_) => MessageType::Response,
+            MessageBody::Error(_) => MessageType::Error
+        }
+    }
+
+    pub fn get_transaction_id(&self) -> &Vec<u8> {
+        &self.transaction_id
+    }
+
+    pub fn get_version(&self) -> Option<&Vec<u8>> {
+        self.version.as_ref()
+    }
+
+    pub fn set_version(&mut self, version: &[u8]) {
+        self.version = Some(version.to_vec());
+    }
+
+    pub fn get_ip(&self) -> Option<&Vec<u8>> {
+        self.ip.as_ref()
+    }
+
+    pub fn set_ip(&mut self, ip: &[u8]) {
+        self.ip = Some(ip.to_vec());
+    }
+
+    pub fn get_ip_address(&self) -> io::Result<Option<SocketAddr>> {
+        match &self.ip {
+            Some(ip) => Ok(Some(decode_peer(ip)?)),
+            None => Ok(None)
+        }
+    }
+
+    pub fn set_ip_address(&mut self, address: &SocketAddr) {
+        self.ip = Some(encode_peer(address));
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
+    pub fn get_body(&self) -> &MessageBody {
+        &self.body
+    }
+
+    pub fn get_query(&self) -> Option<&Query> {
+        match &self.body {
+            MessageBody::Query(query) => Some(query),
+            _ => None
+        }
+    }
+
+    pub fn get_response(&self) -> Option<&Response> {
+        match &self.body {
+            MessageBody::Response(response) => Some(response),
+            _ => None
+        }
+    }
+
+    pub fn get_error(&self) -> Option<&KrpcError> {
+        match &self.body {
+            MessageBody::Error(error) => Some(error),
+            _ => None
+        }
+    }
+}
+
+impl TryFrom<&BencodeObject> for Message {
+
+    type Error = io::Error;
+
+    fn try_from(ben: &BencodeObject) -> io::Result<Self> {
+        let transaction_id = ben.get::<BencodeBytes>("t")
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Transaction id not found"))?
+            .as_bytes().to_vec();
+
+        let kind = ben.get::<BencodeBytes>("y")
+            .and_then(|y| MessageType::from_key(y.as_bytes()))
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Invalid message type"))?;
+
+        let body = match kind {
+            MessageType::Query => {
+                let method = ben.get::<BencodeBytes>("q")
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Query method not found"))?;
+                let method = String::from_utf8(method.as_bytes().to_vec())
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+                let args = ben.get::<BencodeObject>("a")
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Query arguments not found"))?;
+
+                MessageBody::Query(Query::from_args(&method, args)?)
+            }
+            MessageType::Response => {
+                let response = ben.get::<BencodeObject>("r")
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Response body not found"))?;
+
+                MessageBody::Response(Response::try_from(response)?)
+            }
+            MessageType::Error => {
+                let error = ben.get::<BencodeArray>("e")
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Error body not found"))?;
+
+                MessageBody::Error(KrpcError::try_from(error)?)
+            }
+        };
+
+        let read_only = match ben.get::<BencodeNumber>("ro") {
+            Some(ro) => ro.parse::<u8>()? != 0,
+            None => false
+        };
+
+        Ok(Self {
+            transaction_id,
+            version: ben.get::<BencodeBytes>("v").map(|v| v.as_bytes().to_vec()),
+            ip: ben.get::<BencodeBytes>("ip").map(|ip| ip.as_bytes().to_vec()),
+            read_only,
+            body
+        })
+    }
+}
+
+impl From<&Message> for BencodeObject {
+
+    fn from(message: &Message) -> Self {
+        let mut ben = BencodeObject::new();
+
+        match &message.body {
+            MessageBody::Query(query) => {
+                ben.put("a", query.to_args());
+                ben.put("q", query.get_method());
+            }
+            MessageBody::Response(response) => {
+                ben.put("r", BencodeObject::from(response));
+            }
+            MessageBody::Error(error) => {
+                ben.put("e", BencodeArray::from(error));
+            }
+        }
+
+        if let Some(ip) = &message.ip {
+            ben.put("ip", ip.as_slice());
+        }
+
+        if message.read_only {
+            ben.put("ro", 1u8);
+        }
+
+        ben.put("t", message.transaction_id.as_slice());
+
+        if let Some(version) = &message.version {
+            ben.put("v", version.as_slice());
+        }
+
+        ben.put("y", message.get_type().key());
+        ben.sort_top_level_keys();
+        ben
+    }
+}
+
+impl FromBencode for Message {
+
+    fn from_bencode_with_offset(buf: &[u8]) -> io::Result<(Self, usize)> {
+        let (ben, off) = BencodeObject::from_bencode_with_offset(buf)?;
+        Ok((Self::try_from(&ben)?, off))
+    }
+}
+
+impl ToBencode for Message {
+
+    fn to_bencode(&self) -> Vec<u8> {
+        BencodeObject::from(self).to_bencode()
+    }
+}
@@ -0,0 +1,9 @@
+pub mod clock;
+pub mod node_id;
+pub mod secure_node_id;
+pub mod krpc_error;
+pub mod query;
+pub mod response;
+pub mod message;
+pub mod token_manager;
+pub mod transaction_manager;
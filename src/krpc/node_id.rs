@@ -0,0 +1,80 @@
+use std::{fmt, io};
+use std::fmt::Formatter;
+use crate::utils::hex;
+use crate::utils::random::Random;
+
+pub const ID_LENGTH: usize = 20;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct NodeId([u8; ID_LENGTH]);
+
+impl NodeId {
+
+    pub fn new(bytes: [u8; ID_LENGTH]) -> Self {
+        Self(bytes)
+    }
+
+    pub fn from_bytes(buf: &[u8]) -> io::Result<Self> {
+        let bytes: [u8; ID_LENGTH] = buf.try_into()
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Node ID must be 20 bytes"))?;
+        Ok(Self(bytes))
+    }
+
+    pub fn random() -> Self {
+        Self::random_with(&mut Random::from_time())
+    }
+
+    pub(crate) fn random_with(random: &mut Random) -> Self {
+        let mut bytes = [0u8; ID_LENGTH];
+        for chunk in bytes.chunks_mut(8) {
+            let n = random.next_u64().to_be_bytes();
+            chunk.copy_from_slice(&n[..chunk.len()]);
+        }
+        Self(bytes)
+    }
+
+    pub fn from_hex(s: &str) -> io::Result<Self> {
+        Self::from_bytes(&hex::decode(s)?)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; ID_LENGTH] {
+        &self.0
+    }
+
+    pub fn distance(&self, other: &NodeId) -> NodeId {
+        let mut distance = [0u8; ID_LENGTH];
+        for (i, b) in distance.iter_mut().enumerate() {
+            *b = self.0[i] ^ other.0[i];
+        }
+        Self(distance)
+    }
+
+    pub fn leading_zeros(&self) -> usize {
+        match self.0.iter().position(|b| *b != 0) {
+            Some(i) => i * 8 + self.0[i].leading_zeros() as usize,
+            None => ID_LENGTH * 8
+        }
+    }
+
+    pub fn common_prefix(&self, other: &NodeId) -> usize {
+        self.distance(other).leading_zeros()
+    }
+
+    pub fn to_hex(&self) -> String {
+        hex::encode(&self.0)
+    }
+}
+
+impl From<[u8; ID_LENGTH]> for NodeId {
+
+    fn from(bytes: [u8; ID_LENGTH]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl fmt::Display for NodeId {
+
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_hex())
+    }
+}
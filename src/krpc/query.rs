@@ -0,0 +1,248 @@
+use std::io;
+use crate::dht::item::{value_variable, ImmutableItem, Item, MutableItem};
+use crate::krpc::node_id::NodeId;
+use crate::variables::bencode_bytes::BencodeBytes;
+use crate::variables::bencode_number::BencodeNumber;
+use crate::variables::bencode_object::{BencodeObject, GetObject, PutObject};
+use crate::variables::inter::bencode_variable::BencodeVariable;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Query {
+    Ping {
+        id: NodeId
+    },
+    FindNode {
+        id: NodeId,
+        target: NodeId
+    },
+    GetPeers {
+        id: NodeId,
+        info_hash: [u8; 20]
+    },
+    AnnouncePeer {
+        id: NodeId,
+        info_hash: [u8; 20],
+        port: u16,
+        implied_port: bool,
+        token: Vec<u8>
+    },
+    Get {
+        id: NodeId,
+        target: NodeId,
+        seq: Option<i64>
+    },
+    Put {
+        id: NodeId,
+        token: Vec<u8>,
+        item: Item,
+        cas: Option<i64>
+    },
+    Other {
+        method: String,
+        args: BencodeObject
+    }
+}
+
+impl Query {
+
+    pub fn get_method(&self) -> &str {
+        match self {
+            Self::Ping { .. } => "ping",
+            Self::FindNode { .. } => "find_node",
+            Self::GetPeers { .. } => "get_peers",
+            Self::AnnouncePeer { .. } => "announce_peer",
+            Self::Get { .. } => "get",
+            Self::Put { .. } => "put",
+            Self::Other { method, .. } => method
+        }
+    }
+
+    pub fn get_id(&self) -> Option<NodeId> {
+        match self {
+            Self::Ping { id } |
+            Self::FindNode { id, .. } |
+            Self::GetPeers { id, .. } |
+            Self::AnnouncePeer { id, .. } |
+            Self::Get { id, .. } |
+            Self::Put { id, .. } => Some(*id),
+            Self::Other { args, .. } => args.get::<BencodeBytes>("id").and_then(|id| NodeId::from_bytes(id.as_bytes()).ok())
+        }
+    }
+
+    pub fn from_args(method: &str, args: &BencodeObject) -> io::Result<Self> {
+        if let "ping" | "find_node" | "get_peers" | "announce_peer" | "get" | "put" = method {
+            let id = NodeId::from_bytes(args.get::<BencodeBytes>("id")
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Query id not found"))?.as_bytes())?;
+
+            return match method {
+                "ping" => Ok(Self::Ping {
+                    id
+                }),
+                "find_node" => Ok(Self::FindNode {
+                    id,
+                    target: read_target(args)?
+                }),
+                "get" => Ok(Self::Get {
+                    id,
+                    target: read_target(args)?,
+                    seq: read_optional_number(args, "seq")?
+                }),
+                "put" => read_put(id, args),
+                "get_peers" => Ok(Self::GetPeers {
+                    id,
+                    info_hash: read_info_hash(args)?
+                }),
+                _ => {
+                    let port = args.get::<BencodeNumber>("port")
+                        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "announce_peer port not found"))?
+                        .parse::<u16>()?;
+
+                    let implied_port = match args.get::<BencodeNumber>("implied_port") {
+                        Some(implied) => implied.parse::<u8>()? != 0,
+                        None => false
+                    };
+
+                    let token = args.get::<BencodeBytes>("token")
+                        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "announce_peer token not found"))?
+                        .as_bytes().to_vec();
+
+                    Ok(Self::AnnouncePeer {
+                        id,
+                        info_hash: read_info_hash(args)?,
+                        port,
+                        implied_port,
+                        token
+                    })
+                }
+            };
+        }
+
+        Ok(Self::Other {
+            method: method.to_string(),
+            args: args.clone()
+        })
+    }
+
+    pub fn to_args(&self) -> BencodeObject {
+        let mut args = BencodeObject::new();
+
+        match self {
+            Self::Ping { id } => {
+                args.put("id", *id.as_bytes());
+            }
+            Self::FindNode { id, target } => {
+                args.put("id", *id.as_bytes());
+                args.put("target", *target.as_bytes());
+            }
+            Self::GetPeers { id, info_hash } => {
+                args.put("id", *id.as_bytes());
+                args.put("info_hash", *info_hash);
+            }
+            Self::AnnouncePeer { id, info_hash, port, implied_port, token } => {
+                args.put("id", *id.as_bytes());
+                if *implied_port {
+                    args.put("implied_port", 1u8);
+                }
+                args.put("info_hash", *info_hash);
+                args.put("port", *port);
+                args.put("token", token.as_slice());
+            }
+            Self::Get { id, target, seq } => {
+                args.put("id", *id.as_bytes());
+                if let Some(seq) = seq {
+                    args.put("seq", *seq);
+                }
+                args.put("target", *target.as_bytes());
+            }
+            Self::Put { id, token, item, cas } => {
+                args.put("id", *id.as_bytes());
+                args.put("token", token.as_slice());
+
+                if let Some(cas) = cas {
+                    args.put("cas", *cas);
+                }
+
+                if let Item::Mutable(item) = item {
+                    args.put("k", *item.get_public_key());
+                    if !item.get_salt().is_empty() {
+                        args.put("salt", item.get_salt().as_slice());
+                    }
+                    args.put("seq", item.get_seq());
+                    args.put("sig", *item.get_signature());
+                }
+
+                args.put("v", value_variable(item.get_value()));
+            }
+            Self::Other { args: other, .. } => {
+                args = other.clone();
+            }
+        }
+
+        args.sort_top_level_keys();
+        args
+    }
+}
+
+fn read_info_hash(args: &BencodeObject) -> io::Result<[u8; 20]> {
+    args.get::<BencodeBytes>("info_hash")
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Query info_hash not found"))?
+        .as_bytes()
+        .try_into()
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "info_hash must be 20 bytes"))
+}
+
+fn read_target(args: &BencodeObject) -> io::Result<NodeId> {
+    NodeId::from_bytes(args.get::<BencodeBytes>("target")
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Query target not found"))?.as_bytes())
+}
+
+fn read_optional_number(args: &BencodeObject, key: &str) -> io::Result<Option<i64>> {
+    match args.get::<BencodeNumber>(key) {
+        Some(n) => Ok(Some(n.parse::<i64>()?)),
+        None => Ok(None)
+    }
+}
+
+fn read_item(args: &BencodeObject) -> io::Result<Option<Item>> {
+    let value = match args.get_variable("v") {
+        Some(value) => value.to_bencode(),
+        None => return Ok(None)
+    };
+
+    let public_key = match args.get::<BencodeBytes>("k") {
+        Some(k) => k.as_bytes(),
+        None => return Ok(Some(Item::Immutable(ImmutableItem::new(&value)?)))
+    };
+
+    let public_key: [u8; 32] = public_key.try_into()
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Item public key must be 32 bytes"))?;
+
+    let signature: [u8; 64] = args.get::<BencodeBytes>("sig")
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Item signature not found"))?
+        .as_bytes()
+        .try_into()
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Item signature must be 64 bytes"))?;
+
+    let seq = read_optional_number(args, "seq")?
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Item seq not found"))?;
+
+    let salt = args.get::<BencodeBytes>("salt").map(|s| s.as_bytes()).unwrap_or(&[]);
+
+    Ok(Some(Item::Mutable(MutableItem::new(public_key, salt, seq, &value, signature)?)))
+}
+
+fn read_put(id: NodeId, args: &BencodeObject) -> io::Result<Query> {
+    let token = args.get::<BencodeBytes>("token")
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "put token not found"))?
+        .as_bytes().to_vec();
+
+    let item = read_item(args)?
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "put value not found"))?;
+
+    Ok(Query::Put {
+        id,
+        token,
+        item,
+        cas: read_optional_number(args, "cas")?
+    })
+}
@@ -0,0 +1,236 @@
+use std::io;
+use std::net::SocketAddr;
+use crate::compact::address_family::AddressFamily;
+use crate::compact::compact_node::{decode_nodes, encode_nodes, CompactNode};
+use crate::compact::compact_peer::{decode_values, encode_values};
+use crate::dht::item::{check_value, value_variable, ImmutableItem, Item, MutableItem};
+use crate::krpc::node_id::NodeId;
+use crate::variables::bencode_array::{AddArray, BencodeArray};
+use crate::variables::bencode_bytes::BencodeBytes;
+use crate::variables::bencode_number::BencodeNumber;
+use crate::variables::bencode_object::{BencodeObject, GetObject, PutObject};
+use crate::variables::inter::bencode_variable::BencodeVariable;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Response {
+    id: NodeId,
+    nodes: Option<Vec<CompactNode>>,
+    nodes6: Option<Vec<CompactNode>>,
+    values: Option<Vec<SocketAddr>>,
+    token: Option<Vec<u8>>,
+    value: Option<Vec<u8>>,
+    public_key: Option<[u8; 32]>,
+    signature: Option<[u8; 64]>,
+    seq: Option<i64>
+}
+
+impl Response {
+
+    pub fn new(id: NodeId) -> Self {
+        Self {
+            id,
+            nodes: None,
+            nodes6: None,
+            values: None,
+            token: None,
+            value: None,
+            public_key: None,
+            signature: None,
+            seq: None
+        }
+    }
+
+    pub fn get_id(&self) -> &NodeId {
+        &self.id
+    }
+
+    pub fn get_nodes(&self) -> Option<&Vec<CompactNode>> {
+        self.nodes.as_ref()
+    }
+
+    pub fn get_nodes6(&self) -> Option<&Vec<CompactNode>> {
+        self.nodes6.as_ref()
+    }
+
+    pub fn get_all_nodes(&self) -> impl Iterator<Item = &CompactNode> {
+        self.nodes.iter().chain(self.nodes6.iter()).flatten()
+    }
+
+    pub fn add_node(&mut self, node: CompactNode) {
+        match node.get_family() {
+            AddressFamily::V4 => self.nodes.get_or_insert_with(Vec::new).push(node),
+            AddressFamily::V6 => self.nodes6.get_or_insert_with(Vec::new).push(node)
+        }
+    }
+
+    pub fn get_values(&self) -> Option<&Vec<SocketAddr>> {
+        self.values.as_ref()
+    }
+
+    pub fn set_values(&mut self, values: Vec<SocketAddr>) {
+        self.values = Some(values);
+    }
+
+    pub fn get_token(&self) -> Option<&Vec<u8>> {
+        self.token.as_ref()
+    }
+
+    pub fn set_token(&mut self, token: &[u8]) {
+        self.token = Some(token.to_vec());
+    }
+
+    pub fn get_seq(&self) -> Option<i64> {
+        self.seq
+    }
+
+    pub fn set_seq(&mut self, seq: i64) {
+        self.seq = Some(seq);
+    }
+
+    pub fn has_item(&self) -> bool {
+        self.value.is_some()
+    }
+
+    pub fn get_item(&self, salt: &[u8]) -> io::Result<Option<Item>> {
+        let value = match &self.value {
+            Some(value) => value,
+            None => return Ok(None)
+        };
+
+        match (self.public_key, self.signature, self.seq) {
+            (Some(public_key), Some(signature), Some(seq)) => {
+                Ok(Some(Item::Mutable(MutableItem::new(public_key, salt, seq, value, signature)?)))
+            }
+            (None, None, _) => Ok(Some(Item::Immutable(ImmutableItem::new(value)?))),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, "Mutable item is missing k, sig or seq"))
+        }
+    }
+
+    pub fn set_item(&mut self, item: &Item) {
+        self.value = Some(item.get_value().clone());
+
+        if let Item::Mutable(item) = item {
+            self.public_key = Some(*item.get_public_key());
+            self.signature = Some(*item.get_signature());
+            self.seq = Some(item.get_seq());
+        }
+    }
+}
+
+impl TryFrom<&BencodeObject> for Response {
+
+    type Error = io::Error;
+
+    fn try_from(ben: &BencodeObject) -> io::Result<Self> {
+        let id = NodeId::from_bytes(ben.get::<BencodeBytes>("id")
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Response id not found"))?.as_bytes())?;
+
+        let values = match ben.get::<BencodeArray>("values") {
+            Some(list) => {
+                let mut values = Vec::new();
+                for value in list.iter() {
+                    let value = value.as_any().downcast_ref::<BencodeBytes>()
+                        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Response values must be bytes"))?;
+                    values.push(value.as_bytes().to_vec());
+                }
+                Some(decode_values(&values)?)
+            }
+            None => None
+        };
+
+        let nodes = match ben.get::<BencodeBytes>("nodes") {
+            Some(nodes) => Some(decode_nodes(nodes.as_bytes(), AddressFamily::V4)?),
+            None => None
+        };
+
+        let nodes6 = match ben.get::<BencodeBytes>("nodes6") {
+            Some(nodes6) => Some(decode_nodes(nodes6.as_bytes(), AddressFamily::V6)?),
+            None => None
+        };
+
+        let value = match ben.get_variable("v") {
+            Some(value) => {
+                let value = value.to_bencode();
+                check_value(&value)?;
+                Some(value)
+            }
+            None => None
+        };
+
+        let public_key = match ben.get::<BencodeBytes>("k") {
+            Some(k) => Some(k.as_bytes().try_into()
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Item public key must be 32 bytes"))?),
+            None => None
+        };
+
+        let signature = match ben.get::<BencodeBytes>("sig") {
+            Some(sig) => Some(sig.as_bytes().try_into()
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Item signature must be 64 bytes"))?),
+            None => None
+        };
+
+        let seq = match ben.get::<BencodeNumber>("seq") {
+            Some(seq) => Some(seq.parse::<i64>()?),
+            None => None
+        };
+
+        Ok(Self {
+            id,
+            nodes,
+            nodes6,
+            values,
+            token: ben.get::<BencodeBytes>("token").map(|t| t.as_bytes().to_vec()),
+            value,
+            public_key,
+            signature,
+            seq
+        })
+    }
+}
+
+impl From<&Response> for BencodeObject {
+
+    fn from(response: &Response) -> Self {
+        let mut ben = BencodeObject::new();
+        ben.put("id", *response.id.as_bytes());
+
+        if let Some(nodes) = &response.nodes {
+            ben.put("nodes", encode_nodes(nodes, AddressFamily::V4).unwrap_or_default());
+        }
+
+        if let Some(nodes6) = &response.nodes6 {
+            ben.put("nodes6", encode_nodes(nodes6, AddressFamily::V6).unwrap_or_default());
+        }
+
+        if let Some(token) = &response.token {
+            ben.put("token", token.as_slice());
+        }
+
+        if let Some(values) = &response.values {
+            let mut list = BencodeArray::new();
+            for value in encode_values(values) {
+                list.push(value);
+            }
+            ben.put("values", list);
+        }
+
+        if let Some(public_key) = response.public_key {
+            ben.put("k", public_key);
+        }
+
+        if let Some(seq) = response.seq {
+            ben.put("seq", seq);
+        }
+
+        if let Some(signature) = response.signature {
+            ben.put("sig", signature);
+        }
+
+        if let Some(value) = &response.value {
+            ben.put("v", value_variable(value));
+        }
+
+        ben.sort_top_level_keys();
+        ben
+    }
+}
@@ -0,0 +1,52 @@
+use std::net::IpAddr;
+use crate::krpc::node_id::{NodeId, ID_LENGTH};
+use crate::utils::crc32c;
+
+const V4_MASK: [u8; 4] = [0x03, 0x0f, 0x3f, 0xff];
+const V6_MASK: [u8; 8] = [0x01, 0x03, 0x07, 0x0f, 0x1f, 0x3f, 0x7f, 0xff];
+
+pub fn is_exempt_address(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => ip.is_private() || ip.is_loopback() || ip.is_link_local(),
+        IpAddr::V6(ip) => {
+            let first = ip.segments()[0];
+            ip.is_loopback() || (first & 0xfe00) == 0xfc00 || (first & 0xffc0) == 0xfe80
+        }
+    }
+}
+
+fn secure_prefix(ip: &IpAddr, r: u8) -> u32 {
+    let mut buf = match ip {
+        IpAddr::V4(ip) => ip.octets().iter().zip(V4_MASK).map(|(b, m)| b & m).collect::<Vec<u8>>(),
+        IpAddr::V6(ip) => ip.octets().iter().zip(V6_MASK).map(|(b, m)| b & m).collect::<Vec<u8>>()
+    };
+    buf[0] |= (r & 0x07) << 5;
+    crc32c::checksum(&buf)
+}
+
+pub fn secure_node_id(ip: &IpAddr, id: &NodeId) -> NodeId {
+    let mut bytes = *id.as_bytes();
+    let crc = secure_prefix(ip, bytes[ID_LENGTH - 1]);
+
+    bytes[0] = (crc >> 24) as u8;
+    bytes[1] = (crc >> 16) as u8;
+    bytes[2] = ((crc >> 8) as u8 & 0xf8) | (bytes[2] & 0x07);
+    NodeId::new(bytes)
+}
+
+pub fn random_secure_node_id(ip: &IpAddr) -> NodeId {
+    secure_node_id(ip, &NodeId::random())
+}
+
+pub fn is_secure_node_id(id: &NodeId, ip: &IpAddr) -> bool {
+    if is_exempt_address(ip) {
+        return true;
+    }
+
+    let bytes = id.as_bytes();
+    let crc = secure_prefix(ip, bytes[ID_LENGTH - 1]);
+
+    bytes[0] == (crc >> 24) as u8
+        && bytes[1] == (crc >> 16) as u8
+        && (bytes[2] & 0xf8) == ((crc >> 8) as u8 & 0xf8)
+}
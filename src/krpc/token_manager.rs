@@ -0,0 +1,104 @@
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use crate::krpc::clock::Clock;
+use crate::krpc::krpc_error::{ErrorCode, KrpcError};
+use crate::utils::random::Random;
+use crate::utils::sha1::Sha1;
+
+pub const TOKEN_LENGTH: usize = 8;
+pub const DEFAULT_ROTATION_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+pub struct TokenManager {
+    clock: Arc<dyn Clock>,
+    random: Random,
+    interval: Duration,
+    current: [u8; 16],
+    previous: [u8; 16],
+    rotated: Duration
+}
+
+impl TokenManager {
+
+    pub fn new(clock: Arc<dyn Clock>) -> Self {
+        Self::with_random(clock, Random::from_time())
+    }
+
+    pub fn with_seed(clock: Arc<dyn Clock>, seed: u64) -> Self {
+        Self::with_random(clock, Random::new(seed))
+    }
+
+    fn with_random(clock: Arc<dyn Clock>, mut random: Random) -> Self {
+        let current = next_secret(&mut random);
+        let previous = next_secret(&mut random);
+        let rotated = clock.now();
+
+        Self {
+            clock,
+            random,
+            interval: DEFAULT_ROTATION_INTERVAL,
+            current,
+            previous,
+            rotated
+        }
+    }
+
+    pub fn get_interval(&self) -> Duration {
+        self.interval
+    }
+
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.interval = interval;
+    }
+
+    pub fn rotate(&mut self) {
+        self.previous = self.current;
+        self.current = next_secret(&mut self.random);
+        self.rotated = self.clock.now();
+    }
+
+    fn update(&mut self) {
+        let elapsed = self.clock.now().saturating_sub(self.rotated);
+
+        if elapsed >= self.interval * 2 {
+            self.rotate();
+            self.rotate();
+        } else if elapsed >= self.interval {
+            self.rotate();
+        }
+    }
+
+    pub fn generate(&mut self, ip: &IpAddr) -> Vec<u8> {
+        self.update();
+        make_token(ip, &self.current)
+    }
+
+    pub fn validate(&mut self, ip: &IpAddr, token: &[u8]) -> bool {
+        self.update();
+        token == make_token(ip, &self.current).as_slice() || token == make_token(ip, &self.previous).as_slice()
+    }
+
+    pub fn check(&mut self, ip: &IpAddr, token: &[u8]) -> Result<(), KrpcError> {
+        match self.validate(ip, token) {
+            true => Ok(()),
+            false => Err(KrpcError::new(ErrorCode::Protocol, "Bad token"))
+        }
+    }
+}
+
+fn next_secret(random: &mut Random) -> [u8; 16] {
+    let mut secret = [0u8; 16];
+    secret[..8].copy_from_slice(&random.next_u64().to_be_bytes());
+    secret[8..].copy_from_slice(&random.next_u64().to_be_bytes());
+    secret
+}
+
+fn make_token(ip: &IpAddr, secret: &[u8; 16]) -> Vec<u8> {
+    let mut sha = Sha1::new();
+    match ip {
+        IpAddr::V4(ip) => sha.update(&ip.octets()),
+        IpAddr::V6(ip) => sha.update(&ip.octets())
+    }
+    sha.update(secret);
+    sha.finalize()[..TOKEN_LENGTH].to_vec()
+}
@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use crate::krpc::clock::Clock;
+use crate::krpc::message::{Message, MessageType};
+use crate::krpc::node_id::NodeId;
+use crate::krpc::query::Query;
+
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+pub const DEFAULT_MAX_RETRIES: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transaction {
+    id: Vec<u8>,
+    query: Query,
+    address: SocketAddr,
+    node_id: Option<NodeId>,
+    sent: Duration,
+    attempts: u32
+}
+
+impl Transaction {
+
+    pub fn get_id(&self) -> &Vec<u8> {
+        &self.id
+    }
+
+    pub fn get_query(&self) -> &Query {
+        &self.query
+    }
+
+    pub fn get_address(&self) -> &SocketAddr {
+        &self.address
+    }
+
+    pub fn get_node_id(&self) -> Option<&NodeId> {
+        self.node_id.as_ref()
+    }
+
+    pub fn get_sent(&self) -> Duration {
+        self.sent
+    }
+
+    pub fn get_attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    pub fn to_message(&self) -> Message {
+        Message::query(&self.id, self.query.clone())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TimeoutAction {
+    Retry(Message, SocketAddr),
+    Expired(Transaction)
+}
+
+pub struct TransactionManager {
+    clock: Arc<dyn Clock>,
+    timeout: Duration,
+    max_retries: u32,
+    next_id: u16,
+    pending: HashMap<Vec<u8>, Transaction>
+}
+
+impl TransactionManager {
+
+    pub fn new(clock: Arc<dyn Clock>) -> Self {
+        Self {
+            clock,
+            timeout: DEFAULT_TIMEOUT,
+            max_retries: DEFAULT_MAX_RETRIES,
+            next_id: 0,
+            pending: HashMap::new()
+        }
+    }
+
+    pub fn get_timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    pub fn set_timeout(&mut self, timeout: Duration) {
+        self.timeout = timeout;
+    }
+
+    pub fn get_max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    pub fn set_max_retries(&mut self, max_retries: u32) {
+        self.max_retries = max_retries;
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    pub fn get_pending(&self, id: &[u8]) -> Option<&Transaction> {
+        self.pending.get(id)
+    }
+
+    pub fn send(&mut self, query: Query, address: SocketAddr) -> io::Result<Message> {
+        self.send_to(query, address, None)
+    }
+
+    pub fn send_to(&mut self, query: Query, address: SocketAddr, node_id: Option<NodeId>) -> io::Result<Message> {
+        if self.pending.len() > u16::MAX as usize {
+            return Err(io::Error::new(io::ErrorKind::WouldBlock, "No free transaction ids"));
+        }
+
+        let id = loop {
+            let id = self.next_id.to_be_bytes().to_vec();
+            self.next_id = self.next_id.wrapping_add(1);
+            if !self.pending.contains_key(&id) {
+                break id;
+            }
+        };
+
+        let transaction = Transaction {
+            id: id.clone(),
+            query,
+            address,
+            node_id,
+            sent: self.clock.now(),
+            attempts: 1
+        };
+
+        let message = transaction.to_message();
+        self.pending.insert(id, transaction);
+        Ok(message)
+    }
+
+    pub fn receive(&mut self, message: &Message, from: &SocketAddr) -> io::Result<Transaction> {
+        if message.get_type() == MessageType::Query {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Message is a query, not a reply"));
+        }
+
+        self.fail(message.get_transaction_id(), from)
+    }
+
+    pub fn fail(&mut self, id: &[u8], from: &SocketAddr) -> io::Result<Transaction> {
+        let transaction = self.pending.get(id)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Unknown transaction id"))?;
+
+        if transaction.address != *from {
+            return Err(io::Error::new(io::ErrorKind::PermissionDenied, "Reply came from an unexpected address"));
+        }
+
+        Ok(self.pending.remove(id).unwrap())
+    }
+
+    pub fn cancel(&mut self, id: &[u8]) -> Option<Transaction> {
+        self.pending.remove(id)
+    }
+
+    pub fn poll_timeouts(&mut self) -> Vec<TimeoutAction> {
+        let now = self.clock.now();
+        let mut actions = Vec::new();
+
+        let mut expired: Vec<Vec<u8>> = self.pending.iter()
+            .filter(|(_, t)| now.saturating_sub(t.sent) >= self.timeout)
+            .map(|(id, _)| id.clone())
+            .collect();
+        expired.sort();
+
+        for id in expired {
+            let transaction = self.pending.get_mut(&id).unwrap();
+
+            if transaction.attempts <= self.max_retries {
+                transaction.attempts += 1;
+                transaction.sent = now;
+                actions.push(TimeoutAction::Retry(transaction.to_message(), transaction.address));
+            } else {
+                actions.push(TimeoutAction::Expired(self.pending.remove(&id).unwrap()));
+            }
+        }
+
+        actions
+    }
+}
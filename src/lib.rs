@@ -1,4 +1,11 @@
 pub mod variables;
+pub mod metainfo;
+pub mod magnet;
+pub mod krpc;
+pub mod compact;
+pub mod tracker;
+pub mod extension;
+pub mod dht;
 mod utils;
 
 #[macro_export]
@@ -31,13 +38,66 @@ macro_rules! bencode {
 #[cfg(test)]
 mod tests {
 
+    use std::sync::Arc;
+    use std::time::Duration;
     use crate::variables::bencode_object::BencodeObject;
     use crate::variables::bencode_array::BencodeArray;
-    use crate::variables::bencode_object::PutObject;
+    use crate::variables::bencode_object::{GetObject, ObjectOptions, PutObject};
     use crate::variables::bencode_array::AddArray;
     use crate::variables::bencode_bytes::BencodeBytes;
     use crate::variables::bencode_number::BencodeNumber;
     use crate::variables::inter::bencode_variable::{BencodeVariable, FromBencode, ToBencode};
+    use crate::metainfo::torrent::Torrent;
+    use crate::metainfo::info_hash::InfoHash;
+    use crate::magnet::magnet_uri::MagnetUri;
+    use crate::metainfo::torrent_builder::{TorrentBuilder, TorrentVersion};
+    use crate::metainfo::announce_list::AnnounceList;
+    use crate::metainfo::file_attributes::FileAttributes;
+    use crate::metainfo::piece_layout::PieceLayout;
+    use crate::metainfo::safe_path::{sanitize_path, PathIssue, PathPolicy, TextEncoding};
+    use crate::metainfo::web_seeds::SeedListForm;
+    use crate::metainfo::verify::{FileStatus, PieceStatus, TorrentVerifier};
+    use crate::compact::address_family::AddressFamily;
+    use crate::compact::compact_node::{decode_nodes, encode_nodes, CompactNode};
+    use crate::compact::compact_peer::{decode_peers, decode_values, encode_peers, split_peers};
+    use crate::krpc::krpc_error::{ErrorCode, KrpcError};
+    use crate::krpc::message::{Message, MessageType};
+    use crate::krpc::node_id::NodeId;
+    use crate::krpc::query::Query;
+    use crate::krpc::response::Response;
+    use crate::tracker::announce_request::{AnnounceEvent, AnnounceRequest};
+    use crate::tracker::scrape_request::{scrape_url, ScrapeRequest};
+    use crate::tracker::announce_response::{AnnounceResponse, PeerListForm};
+    use crate::tracker::scrape_response::{ScrapeFile, ScrapeResponse};
+    use crate::tracker::tracker_peer::TrackerPeer;
+    use crate::extension::extension_handshake::ExtensionHandshake;
+    use crate::extension::extension_registry::ExtensionRegistry;
+    use crate::extension::metadata_assembler::{MetadataAssembler, PieceState};
+    use crate::extension::metadata_message::{piece_count, split_payload, MetadataMessage, METADATA_PIECE_SIZE};
+    use crate::extension::pex_flags::PexFlags;
+    use crate::extension::pex_message::{PexMessage, MAX_PEX_PEERS};
+    use crate::dht::item::{immutable_target, mutable_target, signing_buffer, ImmutableItem, Item, MutableItem, Signer, Verifier};
+    use crate::dht::item_store::ItemStore;
+    use crate::krpc::clock::{Clock, ManualClock};
+    use crate::krpc::token_manager::TokenManager;
+    use crate::dht::routing_table::{InsertResult, NodeState, RoutingTable};
+    use crate::krpc::transaction_manager::{TimeoutAction, TransactionManager};
+    use crate::dht::dht_node::DhtNode;
+    use crate::dht::peer_store::PeerStore;
+    use crate::dht::simulator::{Behavior, NetworkSimulator};
+    use crate::dht::dht_handler::{DhtHandler, LOOKUP_ALPHA, LOOKUP_RESULT_LIFETIME};
+    use crate::krpc::secure_node_id::{is_exempt_address, is_secure_node_id, random_secure_node_id, secure_node_id};
+    use crate::utils::crc32c;
+    use crate::utils::hex;
+    use crate::utils::sha1::Sha1;
+    use crate::utils::sha256::Sha256;
+
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("rlibbencode-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
 
     #[test]
     fn object() {
@@ -130,4 +190,1224 @@ mod tests {
         assert_eq!(a.to_vec(), b.to_bencode());
         println!("Array nesting encoding and decoding passed.");
     }
+
+    #[test]
+    fn malformed_input() {
+        let inputs: [&[u8]; 10] = [b"", b"l", b"d", b"i12", b"5:abc", b"x", b"d1:a", b"l1:ai1e", b"99999999999999999999999:a", b"1a:b"];
+        for buf in inputs {
+            assert!(BencodeObject::from_bencode(buf).is_err());
+            assert!(BencodeArray::from_bencode(buf).is_err());
+            assert!(BencodeNumber::from_bencode(buf).is_err());
+            assert!(BencodeBytes::from_bencode(buf).is_err());
+            assert!(<Box<dyn BencodeVariable>>::from_bencode(buf).is_err());
+        }
+
+        let value = <Box<dyn BencodeVariable>>::from_bencode(b"d1:ai1ee").unwrap();
+        assert!(value.as_any().downcast_ref::<BencodeObject>().is_some());
+        assert!(BencodeNumber::from_bencode(b"i300e").unwrap().parse::<u8>().is_err());
+        assert!(BencodeNumber::from_bencode(b"i-1e").unwrap().parse::<u32>().is_err());
+        assert_eq!(format!("{}", BencodeBytes::from(vec![0xffu8, 0x00])), "0xff00");
+        println!("Malformed input rejection passed.");
+    }
+
+    #[test]
+    fn hashes() {
+        assert_eq!(hex::encode(&Sha1::digest(b"abc")), "a9993e364706816aba3e25717850c26c9cd0d89d");
+        assert_eq!(hex::encode(&Sha256::digest(b"abc")), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert_eq!(hex::encode(&Sha1::digest(&[b'a'; 1000])), "291e9a6c66994949b57ba5e650361e98fc36b1ba");
+        println!("Hashing passed.");
+    }
+
+    #[test]
+    fn torrent_builder() {
+        let dir = temp_dir("builder");
+        std::fs::create_dir_all(dir.join("data/sub")).unwrap();
+        std::fs::write(dir.join("data/b.bin"), vec![7u8; 40000]).unwrap();
+        std::fs::write(dir.join("data/sub/a.bin"), vec![3u8; 70000]).unwrap();
+        std::fs::write(dir.join("data/empty"), b"").unwrap();
+
+        let builder = TorrentBuilder::new(dir.join("data"))
+            .set_piece_length(32768)
+            .add_tracker("http://tracker.example/announce")
+            .add_tracker_tier(&["udp://a.example:80", "udp://b.example:80"])
+            .add_web_seed("http://seed.example/")
+            .set_comment("TEST")
+            .set_private(true)
+            .set_source("SRC");
+
+        let a = builder.build().unwrap();
+        let b = builder.clone().set_threads(4).build_with_progress(|done, total| assert!(done <= total)).unwrap();
+        assert_eq!(a.to_bencode(), b.to_bencode());
+
+        let c = Torrent::from_bencode(&a.to_bencode()).unwrap();
+        assert_eq!(a.to_bencode(), c.to_bencode());
+        assert_eq!(c.get_info().get_files().len(), 3);
+        assert_eq!(c.get_info().get_files()[0].get_path_string(), "b.bin");
+        assert_eq!(c.get_info().get_pieces().len(), 4);
+        assert!(c.get_info().is_private());
+        assert_eq!(c.get_announce_list().len(), 2);
+
+        let mut data = vec![7u8; 40000];
+        data.extend(vec![3u8; 70000]);
+        assert_eq!(c.get_info().get_pieces()[1], Sha1::digest(&data[32768..65536]));
+
+        let v2 = builder.clone().set_version(TorrentVersion::V2).build().unwrap();
+        let v2 = Torrent::from_bencode(&v2.to_bencode()).unwrap();
+        assert!(v2.get_info().has_v2() && !v2.get_info().has_v1());
+        let file = v2.get_info().get_files().iter().find(|f| f.get_path_string() == "sub/a.bin").unwrap();
+        let layer = v2.get_piece_layer(file.get_pieces_root().unwrap()).unwrap();
+        assert_eq!(layer.len(), 3);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        println!("Torrent building passed.");
+    }
+
+    #[test]
+    fn torrent_verify() {
+        let dir = temp_dir("verify");
+        std::fs::create_dir_all(dir.join("data")).unwrap();
+        std::fs::write(dir.join("data/a.bin"), vec![1u8; 50000]).unwrap();
+        std::fs::write(dir.join("data/b.bin"), vec![2u8; 30000]).unwrap();
+        std::fs::write(dir.join("data/c.bin"), vec![3u8; 20000]).unwrap();
+
+        for version in [TorrentVersion::V1, TorrentVersion::V2] {
+            let builder = TorrentBuilder::new(dir.join("data")).set_piece_length(16384).set_version(version);
+            let torrent = builder.build().unwrap();
+            let report = TorrentVerifier::new(&torrent, &dir).set_threads(2).verify().unwrap();
+            assert!(report.is_complete());
+
+            let mut corrupt = vec![2u8; 30000];
+            corrupt[5000] = 9;
+            std::fs::write(dir.join("data/b.bin"), &corrupt).unwrap();
+            std::fs::remove_file(dir.join("data/c.bin")).unwrap();
+
+            let report = TorrentVerifier::new(&torrent, &dir).verify().unwrap();
+            assert!(!report.is_complete());
+            assert!(!report.get_corrupt_pieces().is_empty());
+            assert!(report.get_missing_pieces().len() >= 2);
+            assert_eq!(report.get_file_status(1), Some(FileStatus::Incomplete));
+            assert_eq!(report.get_file_status(2), Some(FileStatus::Missing));
+            assert!(report.get_affected_files().ends_with(&[1, 2]));
+            assert_eq!(report.get_piece_status(0), Some(PieceStatus::Complete));
+
+            std::fs::write(dir.join("data/b.bin"), vec![2u8; 30000]).unwrap();
+            std::fs::write(dir.join("data/c.bin"), vec![3u8; 20000]).unwrap();
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        println!("Torrent verification passed.");
+    }
+
+    #[test]
+    fn piece_layout() {
+        let layout = PieceLayout::new(16, &[(10, false), (0, false), (6, true), (40, false)], false);
+        assert_eq!(layout.get_piece_count(), 4);
+        assert_eq!(layout.get_file_pieces(0), 0..1);
+        assert_eq!(layout.get_file_pieces(3), 1..4);
+        assert_eq!(layout.get_piece_size(3), 8);
+        assert_eq!(layout.get_piece_slices(0).len(), 2);
+        assert_eq!(layout.locate(12), Some((2, 2)));
+        assert_eq!(layout.locate(16), Some((3, 0)));
+        assert_eq!(layout.locate(56), None);
+
+        let layout = PieceLayout::new(16, &[(10, false), (40, false), (0, false), (5, false)], true);
+        assert_eq!(layout.get_piece_count(), 5);
+        assert_eq!(layout.get_file_pieces(1), 1..4);
+        assert_eq!(layout.get_file_pieces(3), 4..5);
+        assert_eq!(layout.get_piece_size(0), 10);
+        assert_eq!(layout.get_piece_slices(3)[0].get_offset(), 32);
+        assert_eq!(layout.locate(12), None);
+        assert_eq!(layout.locate_piece(4, 3), Some((3, 3)));
+        println!("Piece layout passed.");
+    }
+
+    #[test]
+    fn safe_path() {
+        let path = sanitize_path(&[b"a".to_vec(), b"..".to_vec(), b"CON.txt".to_vec(), b"x\0y/z".to_vec()], TextEncoding::Utf8, PathPolicy::Rewrite).unwrap();
+        assert_eq!(path.get_path(), &std::path::PathBuf::from("a/__/_CON.txt/x_y_z"));
+        assert_eq!(path.get_issues(), &vec![PathIssue::ParentDir, PathIssue::ReservedName, PathIssue::Nul, PathIssue::Separator]);
+        assert!(sanitize_path(&[b"..".to_vec()], TextEncoding::Utf8, PathPolicy::Reject).is_err());
+        assert!(sanitize_path(&[], TextEncoding::Utf8, PathPolicy::Reject).is_err());
+
+        let path = sanitize_path(&[b"caf\xe9 ".to_vec()], TextEncoding::Latin1, PathPolicy::Rewrite).unwrap();
+        assert_eq!(path.get_path(), &std::path::PathBuf::from("caf\u{e9}_"));
+
+        let torrent = Torrent::from_bencode(b"d8:encoding10:ISO-8859-14:infod5:filesld6:lengthi1e4:pathl2:..3:\xe9t\xe9eed6:lengthi1e4:pathl1:a1:be10:path.utf-8l1:c1:deee4:name4:root12:piece lengthi16384e6:pieces20:aaaaaaaaaaaaaaaaaaaaee").unwrap();
+        let paths = crate::metainfo::safe_path::file_paths(&torrent, PathPolicy::Rewrite).unwrap();
+        assert_eq!(paths[0].get_path(), &std::path::PathBuf::from("root/__/\u{e9}t\u{e9}"));
+        assert_eq!(paths[1].get_path(), &std::path::PathBuf::from("root/c/d"));
+        assert!(crate::metainfo::safe_path::file_paths(&torrent, PathPolicy::Reject).is_err());
+        println!("Safe paths passed.");
+    }
+
+    #[test]
+    fn announce_list() {
+        let a = b"ll1:a1:b1:cel1:d1:ael1:bee";
+        let mut list = AnnounceList::from_bencode(a).unwrap();
+        assert_eq!(list.to_bencode(), a.to_vec());
+        assert_eq!(list.get_tracker_count(), 6);
+        list.dedup();
+        assert_eq!(list.get_tiers(), &vec![vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()], vec![b"d".to_vec()]]);
+
+        let odd = AnnounceList::from_bencode(b"llel2:\xffa1:aee").unwrap();
+        assert_eq!(odd.len(), 2);
+        assert_eq!(odd.get_first(), Some("a"));
+        assert_eq!(odd.to_bencode(), b"llel2:\xffa1:aee".to_vec());
+
+        let mut b = list.clone();
+        list.shuffle_with_seed(7);
+        b.shuffle_with_seed(7);
+        assert_eq!(list, b);
+        assert_eq!(list.get_tracker_count(), 4);
+
+        assert!(list.promote("c"));
+        assert_eq!(list.get_tier(0).unwrap()[0], b"c".to_vec());
+        assert!(!list.add_tracker(1, "a"));
+        assert!(list.add_tracker(1, "e"));
+        assert_eq!(AnnounceList::from_bencode(&list.to_bencode()).unwrap(), list);
+
+        let torrent = Torrent::from_bencode(b"d8:announce1:x4:infod6:lengthi1e4:name1:n12:piece lengthi16384e6:pieces20:aaaaaaaaaaaaaaaaaaaaee").unwrap();
+        assert_eq!(torrent.get_trackers().get_first().unwrap(), "x");
+        println!("Announce list passed.");
+    }
+
+    #[test]
+    fn web_seeds() {
+        let a = b"d9:httpseedsl18:http://h.example/a3:bad18:http://h.example/ae4:infod6:lengthi1e4:name1:n12:piece lengthi16384e6:pieces20:aaaaaaaaaaaaaaaaaaaae8:url-list17:http://w.example/e";
+        let torrent = Torrent::from_bencode(a).unwrap();
+        assert_eq!(torrent.get_url_list().get_form(), SeedListForm::Single);
+        assert_eq!(torrent.get_url_list().get_urls(), &vec!["http://w.example/".to_string()]);
+        assert_eq!(torrent.get_http_seeds().get_form(), SeedListForm::List);
+        assert_eq!(torrent.get_http_seeds().len(), 1);
+
+        let b = Torrent::from_bencode(&torrent.to_bencode()).unwrap();
+        assert_eq!(torrent, b);
+        assert!(torrent.to_bencode().ends_with(b"8:url-list17:http://w.example/e"));
+        assert_eq!(torrent.to_bencode(), a.to_vec());
+
+        let c = b"d4:infod6:lengthi1e4:name1:n12:piece lengthi16384e6:pieces20:aaaaaaaaaaaaaaaaaaaae8:url-list3:\xffzze";
+        let torrent = Torrent::from_bencode(c).unwrap();
+        assert!(torrent.get_url_list().is_empty());
+        assert_eq!(torrent.get_url_list().get_rejected(), vec![&b"\xffzz"[..]]);
+        assert_eq!(torrent.to_bencode(), c.to_vec());
+
+        assert!(crate::metainfo::web_seeds::validate_url("https://user@host:80/p").is_ok());
+        assert!(crate::metainfo::web_seeds::validate_url("udp://host:80").is_err());
+        assert!(crate::metainfo::web_seeds::validate_url("http:///path").is_err());
+        println!("Web seeds passed.");
+    }
+
+    #[test]
+    fn padding_files() {
+        let attributes = FileAttributes::from(&b"xhp"[..]);
+        assert!(attributes.is_padding() && attributes.is_executable() && attributes.is_hidden() && !attributes.is_symlink());
+        assert_eq!(attributes.to_bytes(), b"phx".to_vec());
+
+        let dir = temp_dir("padding");
+        std::fs::create_dir_all(dir.join("data")).unwrap();
+        std::fs::write(dir.join("data/a.bin"), vec![1u8; 20000]).unwrap();
+        std::fs::write(dir.join("data/b.bin"), vec![2u8; 40000]).unwrap();
+        std::fs::write(dir.join("data/c.bin"), vec![3u8; 100]).unwrap();
+
+        let torrent = TorrentBuilder::new(dir.join("data"))
+            .set_piece_length(16384)
+            .set_version(TorrentVersion::Hybrid)
+            .build().unwrap();
+        let torrent = Torrent::from_bencode(&torrent.to_bencode()).unwrap();
+        let info = torrent.get_info();
+        assert!(info.has_v1() && info.has_v2());
+
+        let files = info.get_files();
+        assert_eq!(files.len(), 5);
+        assert!(files[1].is_padding() && files[3].is_padding());
+        assert_eq!(files[1].get_length(), 32768 - 20000);
+        assert_eq!(files[1].get_path_string(), format!(".pad/{}", 32768 - 20000));
+        assert!(files[2].get_pieces_root().is_some());
+
+        let layout = PieceLayout::from(info);
+        assert_eq!(layout.get_file_pieces(2), 2..5);
+        assert_eq!(layout.get_piece_slices(1).len(), 2);
+        assert_eq!(layout.get_data_slices(1).len(), 1);
+
+        let report = TorrentVerifier::new(&torrent, &dir).verify().unwrap();
+        assert!(report.is_complete());
+
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink("..", dir.join("data/loop")).unwrap();
+            std::os::unix::fs::symlink("a.bin", dir.join("data/link")).unwrap();
+
+            let plain = TorrentBuilder::new(dir.join("data")).set_piece_length(16384).build().unwrap();
+            assert_eq!(plain.get_info().get_files().len(), 3);
+
+            let detected = TorrentBuilder::new(dir.join("data"))
+                .set_piece_length(16384)
+                .set_detect_attributes(true)
+                .build().unwrap();
+            let detected = Torrent::from_bencode(&detected.to_bencode()).unwrap();
+            let files = detected.get_info().get_files();
+            assert_eq!(files.len(), 4);
+            assert_eq!(files[3].get_path_string(), "link");
+            assert!(files[3].get_attributes().is_symlink());
+            assert_eq!(files[3].get_symlink_path(), Some(&vec![b"a.bin".to_vec()]));
+            assert_eq!(files[3].get_length(), 0);
+            assert_eq!(detected.get_info().get_pieces(), plain.get_info().get_pieces());
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        println!("Padding files passed.");
+    }
+
+    #[test]
+    fn magnet_uri() {
+        let a = "magnet:?xt=urn:btih:c12fe1c06bba254a9dc9f519b335aa7c1367a88a&dn=Test+File%21&tr=udp%3A%2F%2Ft.example%3A80&tr.1=http%3A%2F%2Fu.example%2Fa&ws=http%3A%2F%2Fw.example%2F&x.pe=1.2.3.4%3A6881&so=0,2,4-6";
+        let magnet: MagnetUri = a.parse().unwrap();
+        assert_eq!(magnet.get_info_hash().unwrap().to_hex(), "c12fe1c06bba254a9dc9f519b335aa7c1367a88a");
+        assert_eq!(magnet.get_display_name().unwrap(), "Test File!");
+        assert_eq!(magnet.get_trackers().len(), 2);
+        assert_eq!(magnet.get_peers(), &vec!["1.2.3.4:6881".to_string()]);
+        assert_eq!(magnet.get_select_only(), &vec![0..=0, 2..=2, 4..=6]);
+        assert!(magnet.is_selected(5) && !magnet.is_selected(3));
+        assert_eq!(magnet.to_string().parse::<MagnetUri>().unwrap(), magnet);
+
+        let b: MagnetUri = "magnet:?xt=urn:btih:YEX6DQDLXISUVHOJ6UM3GNNKPQJWPKEK".parse().unwrap();
+        assert_eq!(b.get_info_hash(), magnet.get_info_hash());
+
+        let v2 = InfoHash::V2([7u8; 32]);
+        let c: MagnetUri = MagnetUri::from_info_hash(v2).to_string().parse().unwrap();
+        assert_eq!(c.get_info_hash_v2(), Some(&v2));
+        assert!(c.to_string().contains("urn:btmh:1220"));
+        assert!("magnet:?dn=x".parse::<MagnetUri>().is_err());
+
+        let torrent = Torrent::from_bencode(b"d8:announce1:x4:infod6:lengthi1e4:name1:n12:piece lengthi16384e6:pieces20:aaaaaaaaaaaaaaaaaaaaee").unwrap();
+        let d = MagnetUri::from_torrent(&torrent);
+        assert_eq!(d.get_info_hash(), Some(&torrent.get_info_hash()));
+        assert_eq!(d.get_trackers(), &vec!["x".to_string()]);
+        println!("Magnet URI passed.");
+    }
+
+    #[test]
+    fn krpc_messages() {
+        let ping = b"d1:ad2:id20:abcdefghij0123456789e1:q4:ping1:t2:aa1:y1:qe";
+        let message = Message::from_bencode(ping).unwrap();
+        assert_eq!(message.get_type(), MessageType::Query);
+        assert_eq!(message.get_transaction_id(), &b"aa".to_vec());
+        assert_eq!(message.get_query().unwrap().get_method(), "ping");
+        assert_eq!(message.to_bencode(), ping.to_vec());
+
+        let announce = b"d1:ad2:id20:abcdefghij012345678912:implied_porti1e9:info_hash20:mnopqrstuvwxyz1234564:porti6881e5:token8:aoeusnthe1:q13:announce_peer1:t2:aa1:y1:qe";
+        let message = Message::from_bencode(announce).unwrap();
+        match message.get_query().unwrap() {
+            Query::AnnouncePeer { port, implied_port, token, .. } => {
+                assert_eq!(*port, 6881);
+                assert!(*implied_port);
+                assert_eq!(token, b"aoeusnth");
+            }
+            _ => panic!("Expected announce_peer")
+        }
+        assert_eq!(message.to_bencode(), announce.to_vec());
+
+        let mut response = Response::new(NodeId::new([1u8; 20]));
+        response.set_token(b"tok");
+        response.set_values(vec!["127.0.0.1:6881".parse().unwrap()]);
+        let mut message = Message::response(b"bb", response.clone());
+        message.set_version(b"RB01");
+        message.set_ip(&[10, 0, 0, 1, 0x1a, 0xe1]);
+        message.set_read_only(true);
+        let decoded = Message::from_bencode(&message.to_bencode()).unwrap();
+        assert_eq!(decoded, message);
+        assert_eq!(decoded.get_response(), Some(&response));
+        assert!(decoded.is_read_only());
+
+        let error = b"d1:eli203e14:Protocol Errore1:t2:aa1:y1:ee";
+        let message = Message::from_bencode(error).unwrap();
+        assert_eq!(message.get_error(), Some(&KrpcError::from(ErrorCode::Protocol)));
+        assert_eq!(message.to_bencode(), error.to_vec());
+
+        assert!(Message::from_bencode(b"d1:t2:aa1:y1:qe").is_err());
+        assert!(Message::from_bencode(b"d1:ad2:id3:abce1:q4:ping1:t2:aa1:y1:qe").is_err());
+        assert!(Message::from_bencode(b"d1:ad2:id20:abc").is_err());
+        assert!(Message::from_bencode(b"d1:x99999999999999999999999:ae").is_err());
+        println!("KRPC messages passed.");
+    }
+
+    #[test]
+    fn compact_codecs() {
+        let v4: std::net::SocketAddr = "10.1.2.3:6881".parse().unwrap();
+        let v6: std::net::SocketAddr = "[2001:db8::1]:51413".parse().unwrap();
+
+        let peers = encode_peers(&[v4], AddressFamily::V4).unwrap();
+        assert_eq!(peers, vec![10, 1, 2, 3, 0x1a, 0xe1]);
+        assert_eq!(decode_peers(&peers, AddressFamily::V4).unwrap(), vec![v4]);
+        assert!(encode_peers(&[v6], AddressFamily::V4).is_err());
+        assert!(decode_peers(&peers[..5], AddressFamily::V4).is_err());
+
+        let (a, b) = split_peers(&[v4, v6, v4]);
+        assert_eq!((a.len(), b.len()), (12, 18));
+        assert_eq!(decode_peers(&b, AddressFamily::V6).unwrap(), vec![v6]);
+        assert_eq!(decode_values(&[a[..6].to_vec(), b.clone()]).unwrap(), vec![v4, v6]);
+        assert!(decode_values(&[vec![1, 2, 3]]).is_err());
+
+        let n4 = CompactNode::new(NodeId::new([3u8; 20]), v4);
+        let n6 = CompactNode::new(NodeId::new([4u8; 20]), v6);
+        let nodes = encode_nodes(&[n4, n4], AddressFamily::V4).unwrap();
+        assert_eq!(nodes.len(), 52);
+        assert_eq!(decode_nodes(&nodes, AddressFamily::V4).unwrap(), vec![n4, n4]);
+        assert!(decode_nodes(&nodes[..50], AddressFamily::V4).is_err());
+        assert_eq!(decode_nodes(&n6.encode(), AddressFamily::V6).unwrap(), vec![n6]);
+
+        let mut response = Response::new(NodeId::new([1u8; 20]));
+        response.add_node(n4);
+        response.add_node(n6);
+        let decoded = Message::from_bencode(&Message::response(b"aa", response).to_bencode()).unwrap();
+        let decoded = decoded.get_response().unwrap();
+        assert_eq!(decoded.get_nodes(), Some(&vec![n4]));
+        assert_eq!(decoded.get_nodes6(), Some(&vec![n6]));
+        assert_eq!(decoded.get_all_nodes().count(), 2);
+        println!("Compact codecs passed.");
+    }
+
+    #[test]
+    fn announce_response() {
+        let compact = b"d8:completei5e10:incompletei2e8:intervali1800e12:min intervali900e5:peers6:\x0a\x00\x00\x01\x1a\xe16:peers618:\x20\x01\x0d\xb8\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x01\x1a\xe110:tracker id3:abc15:warning message4:slowe";
+        let response = AnnounceResponse::from_bencode(compact).unwrap();
+        assert_eq!(response.get_interval(), Some(1800));
+        assert_eq!(response.get_min_interval(), Some(900));
+        assert_eq!((response.get_complete(), response.get_incomplete()), (Some(5), Some(2)));
+        assert_eq!(response.get_tracker_id(), Some(&b"abc".to_vec()));
+        assert_eq!(response.get_warning_message().unwrap(), "slow");
+        assert_eq!(response.get_peers().len(), 2);
+        assert_eq!(response.get_peers()[1].get_address(), Some("[2001:db8::1]:6881".parse().unwrap()));
+        assert_eq!(response.to_bencode(), compact.to_vec());
+
+        let dictionary = b"d8:intervali60e5:peersld2:ip11:example.org7:peer id20:-RB0001-0123456789ab4:porti6881eeee";
+        let response = AnnounceResponse::from_bencode(dictionary).unwrap();
+        assert_eq!(response.get_peer_form(), PeerListForm::Dictionary);
+        assert_eq!(response.get_peers()[0].get_host(), "example.org");
+        assert_eq!(response.get_peers()[0].get_address(), None);
+        assert_eq!(response.to_bencode(), dictionary.to_vec());
+
+        let split = b"d8:intervali60e5:peersld2:ip8:10.0.0.14:porti1eee6:peers6ld2:ip3:::14:porti2eeee";
+        let response = AnnounceResponse::from_bencode(split).unwrap();
+        assert_eq!(response.get_peer_form(), PeerListForm::Dictionary);
+        assert_eq!(response.get_peers().len(), 2);
+        assert_eq!(response.get_peers()[1].get_address(), Some("[::1]:2".parse().unwrap()));
+
+        let mut named = AnnounceResponse::new(60);
+        named.add_peer(TrackerPeer::from("10.0.0.1:1".parse::<std::net::SocketAddr>().unwrap()));
+        named.add_peer(TrackerPeer::new("example.org", 6881));
+        let decoded = AnnounceResponse::from_bencode(&named.to_bencode()).unwrap();
+        assert_eq!(decoded.get_peer_form(), PeerListForm::Dictionary);
+        assert_eq!(decoded.get_peers().len(), 2);
+        assert_eq!(decoded.get_peers()[1].get_host(), "example.org");
+
+        let mut built = AnnounceResponse::new(120);
+        built.add_peer(TrackerPeer::from("127.0.0.1:51413".parse::<std::net::SocketAddr>().unwrap()));
+        assert_eq!(AnnounceResponse::from_bencode(&built.to_bencode()).unwrap(), built);
+
+        let failure = AnnounceResponse::from_bencode(b"d14:failure reason9:not founde").unwrap();
+        assert!(failure.is_failure());
+        assert_eq!(failure, AnnounceResponse::failure("not found"));
+        assert_eq!(failure.to_bencode(), b"d14:failure reason9:not founde".to_vec());
+        assert!(AnnounceResponse::from_bencode(b"d5:peers5:abcdee").is_err());
+        println!("Announce response passed.");
+    }
+
+    #[test]
+    fn scrape_response() {
+        let mut buf = b"d5:filesd20:".to_vec();
+        buf.extend_from_slice(&[0xfeu8; 20]);
+        buf.extend_from_slice(b"d8:completei0e10:downloadedi0e10:incompletei1e4:name2:\xff\xfe5:x-tagi7ee20:");
+        buf.extend_from_slice(&[0xffu8; 20]);
+        buf.extend_from_slice(b"d8:completei5e10:downloadedi50e10:incompletei10e4:name4:testee5:flagsd20:min_request_intervali3600e8:x-customi1ee7:x-notes2:hie");
+
+        let response = ScrapeResponse::from_bencode(&buf).unwrap();
+        assert_eq!(response.get_files().len(), 2);
+        let file = response.get_file(&InfoHash::V1([0xff; 20])).unwrap();
+        assert_eq!((file.get_complete(), file.get_downloaded(), file.get_incomplete()), (5, 50, 10));
+        assert_eq!(file.get_name().unwrap(), "test");
+        let other = response.get_file(&InfoHash::V1([0xfe; 20])).unwrap();
+        assert_eq!(other.get_name(), None);
+        assert_eq!(other.get_name_bytes(), Some(&vec![0xff, 0xfe]));
+        assert!(other.get_extra().get::<BencodeNumber>("x-tag").is_some());
+        assert!(response.get_extra().get::<BencodeBytes>("x-notes").is_some());
+        assert_eq!(response.get_min_request_interval(), Some(3600));
+        assert_eq!(response.to_bencode(), buf);
+
+        let ben = BencodeObject::from_bencode(&buf).unwrap();
+        assert!(ben.to_string().contains(&format!("0x{}", "ff".repeat(20))));
+
+        let mut built = ScrapeResponse::new();
+        built.add_file(&InfoHash::V2([9u8; 32]), ScrapeFile::new(1, 2, 3));
+        built.add_file(&InfoHash::V1([1u8; 20]), ScrapeFile::new(0, 0, 0));
+        let encoded = built.to_bencode();
+        assert_eq!(&encoded[12..32], &[1u8; 20]);
+        let decoded = ScrapeResponse::from_bencode(&built.to_bencode()).unwrap();
+        assert_eq!(decoded, built);
+        assert!(decoded.get_file(&InfoHash::V2([9u8; 32])).is_some());
+
+        let failure = ScrapeResponse::from_bencode(b"d14:failure reason6:deniede").unwrap();
+        assert_eq!(failure, ScrapeResponse::failure("denied"));
+        assert_eq!(failure.to_bencode(), b"d14:failure reason6:deniede".to_vec());
+        let failure = ScrapeResponse::from_bencode(b"d14:failure reason2:\xc3\x28e").unwrap();
+        assert!(failure.is_failure() && failure.get_failure_reason().is_none());
+        assert_eq!(failure.to_bencode(), b"d14:failure reason2:\xc3\x28e".to_vec());
+        assert!(ScrapeResponse::from_bencode(b"d5:filesd3:abcd8:completei0e10:downloadedi0e10:incompletei0eeee").is_err());
+        println!("Scrape response passed.");
+    }
+
+    #[test]
+    fn tracker_requests() {
+        let info_hash = InfoHash::from_hex("c12fe1c06bba254a9dc9f519b335aa7c1367a88a").unwrap();
+        let mut request = AnnounceRequest::new(info_hash, *b"-RB0001-0123456789ab", 6881);
+        request.set_left(1024);
+        request.set_event(AnnounceEvent::Started);
+        request.set_numwant(50);
+        request.set_key("k 1");
+        request.set_tracker_id(&[0x00, 0xff]);
+
+        let url = request.to_url("http://tracker.example/announce?passkey=abc");
+        assert!(url.starts_with("http://tracker.example/announce?passkey=abc&info_hash=%C1%2F%E1%C0k%BA%25J%9D%C9%F5%19%B35%AA%7C%13g%A8%8A&peer_id=-RB0001-0123456789ab"));
+        assert!(url.contains("&compact=1&event=started&numwant=50&key=k%201&trackerid=%00%FF"));
+        assert_eq!(AnnounceRequest::from_url(&url).unwrap(), request);
+
+        let v2 = AnnounceRequest::new(InfoHash::V2([7u8; 32]), [1u8; 20], 1);
+        assert_eq!(v2.get_info_hash(), &InfoHash::V1([7u8; 20]));
+        assert!(AnnounceRequest::from_query("info_hash=abc&peer_id=x&port=1").is_err());
+        assert!(AnnounceRequest::from_query("port=1").is_err());
+
+        let mut scrape = ScrapeRequest::new();
+        scrape.add_info_hash(info_hash);
+        scrape.add_info_hash(InfoHash::V1([0xff; 20]));
+        let scrape_base = scrape_url("http://tracker.example/x/announce.php?passkey=abc").unwrap();
+        assert_eq!(scrape_base, "http://tracker.example/x/scrape.php?passkey=abc");
+        assert_eq!(ScrapeRequest::from_url(&scrape.to_url(&scrape_base)).unwrap(), scrape);
+        assert_eq!(scrape_url("http://tracker.example/a"), None);
+        println!("Tracker requests passed.");
+    }
+
+    #[test]
+    fn extension_handshake() {
+        let buf = b"d1:ei0e4:ipv416:\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\xff\xff\x0a\x00\x00\x011:md11:ut_metadatai3e6:ut_pexi0ee13:metadata_sizei31235e1:pi6881e4:reqqi500e11:upload_onlyi1e1:v13:\xc2\xb5Torrent 1.26:yourip4:\x7f\x00\x00\x01e";
+        let handshake = ExtensionHandshake::from_bencode(buf).unwrap();
+        assert_eq!(handshake.get_extension("ut_metadata"), Some(3));
+        assert_eq!(handshake.get_port(), Some(6881));
+        assert_eq!(handshake.get_client().unwrap(), "\u{b5}Torrent 1.2");
+        assert_eq!(handshake.get_your_ip(), Some("127.0.0.1".parse().unwrap()));
+        assert_eq!(handshake.get_ipv4(), None);
+        assert_eq!(handshake.get_reqq(), Some(500));
+        assert_eq!(handshake.get_metadata_size(), Some(31235));
+        assert!(handshake.is_upload_only());
+        assert!(handshake.get_extra().contains_key("e"));
+        assert!(handshake.get_extra().contains_key("ipv4"));
+        assert_eq!(handshake.to_bencode(), buf.to_vec());
+
+        let mut local = ExtensionRegistry::new();
+        assert_eq!(local.register("ut_metadata"), Some(1));
+        assert_eq!(local.register("ut_pex"), Some(2));
+        assert_eq!(local.register("ut_metadata"), Some(1));
+        assert!(!local.register_with_id("lt_donthave", 2));
+
+        local.update_remote(&handshake);
+        assert_eq!(local.get_remote_id("ut_metadata"), Some(3));
+        assert_eq!(local.get_local_name(2).unwrap(), "ut_pex");
+        assert!(local.supports("ut_metadata") && !local.supports("ut_pex"));
+
+        let ours = ExtensionHandshake::from_bencode(&local.to_handshake().to_bencode()).unwrap();
+        assert_eq!(ours.get_extensions(), &vec![(b"ut_metadata".to_vec(), 1), (b"ut_pex".to_vec(), 2)]);
+
+        let odd = b"d1:md11:ut_metadatai2e6:ut_pexi300e2:\xff\xfei4ee1:pi70000e4:reqqi500e1:vi7ee";
+        let handshake = ExtensionHandshake::from_bencode(odd).unwrap();
+        assert_eq!(handshake.get_extension("ut_metadata"), Some(2));
+        assert_eq!(handshake.get_extensions()[1], (b"\xff\xfe".to_vec(), 4));
+        assert_eq!(handshake.get_port(), None);
+        assert_eq!(handshake.get_reqq(), Some(500));
+        assert_eq!(handshake.get_client(), None);
+        assert!(handshake.get_extra().contains_key("p") && handshake.get_extra().contains_key("v"));
+        assert_eq!(handshake.to_bencode(), odd.to_vec());
+        println!("Extension handshake passed.");
+    }
+
+    #[test]
+    fn metadata_message() {
+        let request = MetadataMessage::Request { piece: 0 };
+        assert_eq!(request.to_bencode(), b"d8:msg_typei0e5:piecei0ee".to_vec());
+        assert_eq!(MetadataMessage::from_bencode(b"d8:msg_typei0e5:piecei0ee").unwrap(), request);
+
+        let mut buf = b"d8:msg_typei1e5:piecei1e10:total_sizei20000ee".to_vec();
+        buf.extend_from_slice(&[7u8; 20000 - METADATA_PIECE_SIZE]);
+        let (dict, payload) = split_payload(&buf).unwrap();
+        assert_eq!(dict.get::<BencodeNumber>("total_size").unwrap().parse::<u64>().unwrap(), 20000);
+        assert_eq!(payload.len(), 3616);
+
+        let data = MetadataMessage::from_bencode(&buf).unwrap();
+        assert_eq!(data.get_piece(), 1);
+        match &data {
+            MetadataMessage::Data { payload, .. } => assert_eq!(payload, &vec![7u8; 3616]),
+            _ => panic!("Expected data message")
+        }
+        assert_eq!(data.to_bencode(), buf);
+        assert_eq!(piece_count(20000), 2);
+
+        assert!(MetadataMessage::from_bencode(&buf[..buf.len() - 1]).is_err());
+        assert!(MetadataMessage::from_bencode(b"d8:msg_typei1e5:piecei2e10:total_sizei20000ee").is_err());
+        assert!(MetadataMessage::from_bencode(b"d8:msg_typei2e5:piecei0eeXX").is_err());
+        assert!(MetadataMessage::from_bencode(b"d8:msg_typei9e5:piecei0ee").is_err());
+        println!("Metadata message passed.");
+    }
+
+    #[test]
+    fn metadata_assembler() {
+        let mut info = b"d6:lengthi16384000e4:name1:n12:piece lengthi16384e6:pieces20000:".to_vec();
+        info.extend_from_slice(&[0xabu8; 20000]);
+        info.push(b'e');
+        let info_hash = InfoHash::v1_from_info(&info);
+
+        let uri = format!("magnet:?xt=urn:btih:{}&tr=http%3A%2F%2Fa%2Fannounce&tr=http%3A%2F%2Fb%2Fannounce", info_hash.to_hex());
+        let mut assembler = MetadataAssembler::from_magnet(&uri.parse().unwrap()).unwrap();
+        let data = |piece: u32| MetadataMessage::Data {
+            piece,
+            total_size: info.len() as u64,
+            payload: info[piece as usize * METADATA_PIECE_SIZE..info.len().min((piece as usize + 1) * METADATA_PIECE_SIZE)].to_vec()
+        };
+
+        assert!(!assembler.receive(&data(0)).unwrap());
+        assert_eq!(assembler.get_piece_count(), 2);
+        assert_eq!(assembler.next_request(), Some(MetadataMessage::Request { piece: 1 }));
+        assert_eq!(assembler.next_request(), None);
+        assembler.receive(&MetadataMessage::Reject { piece: 1 }).unwrap();
+        assert_eq!(assembler.get_piece_state(1), Some(PieceState::Missing));
+
+        let wrong = MetadataMessage::Data { piece: 1, total_size: 5, payload: vec![0; 5] };
+        assert!(assembler.receive(&wrong).is_err());
+        assert!(assembler.receive(&data(1)).unwrap());
+
+        let torrent = assembler.to_torrent().unwrap();
+        assert_eq!(torrent.get_info_hash(), info_hash);
+        assert_eq!(torrent.get_trackers().get_tracker_count(), 2);
+        assert_eq!(torrent.get_info().get_piece_count(), 1000);
+
+        let mut corrupt = MetadataAssembler::new(InfoHash::V1([0u8; 20]));
+        corrupt.receive(&data(0)).unwrap();
+        corrupt.receive(&data(1)).unwrap();
+        assert!(corrupt.verify().is_err());
+        assert_eq!(corrupt.get_received_count(), 0);
+        println!("Metadata assembler passed.");
+    }
+
+    #[test]
+    fn pex_message() {
+        let buf = b"d5:added12:\x0a\x00\x00\x01\x1a\xe1\x0a\x00\x00\x02\x1a\xe17:added.f2:\x03\x106:added60:8:added6.f0:7:dropped6:\x0a\x00\x00\x03\x1a\xe18:dropped60:e";
+        let message = PexMessage::from_bencode(buf).unwrap();
+        assert_eq!(message.get_added().len(), 2);
+        let (addr, flags) = message.get_added()[0];
+        assert_eq!(addr, "10.0.0.1:6881".parse().unwrap());
+        assert!(flags.is_encryption() && flags.is_seed() && !flags.is_utp());
+        assert!(message.get_added()[1].1.is_outgoing());
+        assert_eq!(message.get_dropped(), &vec!["10.0.0.3:6881".parse().unwrap()]);
+        assert_eq!(message.to_bencode(), buf.to_vec());
+
+        let mut utp = PexFlags::new();
+        utp.set_utp(true);
+        let a = ("10.0.0.1:1".parse().unwrap(), PexFlags::new());
+        let b = ("[::1]:2".parse().unwrap(), utp);
+        let c = ("10.0.0.3:3".parse().unwrap(), PexFlags::from(0x08));
+        let diff = PexMessage::diff(&[a, c], &[a, b]);
+        assert_eq!(diff.len(), 1);
+        let diff = &diff[0];
+        assert_eq!(diff.get_added(), &vec![b]);
+        assert_eq!(diff.get_dropped(), &vec![c.0]);
+        assert_eq!(&PexMessage::from_bencode(&diff.to_bencode()).unwrap(), diff);
+        assert!(PexMessage::diff(&[a], &[a]).is_empty());
+
+        let many: Vec<_> = (0..120u16).map(|p| (std::net::SocketAddr::from(([10, 0, 0, 1], p)), PexFlags::new())).collect();
+        let split = PexMessage::diff(&many[..10], &many[10..]);
+        assert_eq!(split.iter().map(|m| m.get_added().len()).collect::<Vec<_>>(), vec![MAX_PEX_PEERS, MAX_PEX_PEERS, 10]);
+        assert_eq!(split.iter().map(|m| m.get_dropped().len()).collect::<Vec<_>>(), vec![10, 0, 0]);
+        assert_eq!(split[2].get_added().last().unwrap().0, many[119].0);
+
+        let mut over = BencodeObject::new();
+        over.put("added", vec![0u8; 6 * 51]);
+        assert_eq!(PexMessage::try_from(&over).unwrap().get_added().len(), MAX_PEX_PEERS);
+        assert!(PexMessage::from_bencode(b"d5:added6:\x0a\x00\x00\x01\x1a\xe17:added.f2:\x01\x01e").is_err());
+        println!("PEX message passed.");
+    }
+
+    struct TestSigner;
+
+    impl Signer for TestSigner {
+
+        fn get_public_key(&self) -> [u8; 32] {
+            [5u8; 32]
+        }
+
+        fn sign(&self, message: &[u8]) -> [u8; 64] {
+            let mut signature = [0u8; 64];
+            signature[..32].copy_from_slice(&Sha256::digest(message));
+            signature
+        }
+    }
+
+    impl Verifier for TestSigner {
+
+        fn verify(&self, public_key: &[u8; 32], message: &[u8], signature: &[u8; 64]) -> bool {
+            *public_key == self.get_public_key() && self.sign(message) == *signature
+        }
+    }
+
+    #[test]
+    fn dht_items() {
+        assert_eq!(signing_buffer(b"foobar", 1, b"12:Hello World!"), b"4:salt6:foobar3:seqi1e1:v12:Hello World!".to_vec());
+        assert_eq!(signing_buffer(b"", 1, b"12:Hello World!"), b"3:seqi1e1:v12:Hello World!".to_vec());
+        assert_eq!(immutable_target(b"12:Hello World!").to_hex(), "e5f96f6f38320f0f33959cb4d3d656452117aadb");
+
+        let key: [u8; 32] = hex::decode("77ff84905a91936367c01360803104f92432fcd904a43511876df5cdf3e7e548").unwrap().try_into().unwrap();
+        assert_eq!(mutable_target(&key, b"foobar").to_hex(), "411eba73b6f087ca51a3795d9c8c938d365e32c1");
+        assert_eq!(mutable_target(&key, b"").to_hex(), "4a533d47ec9c7d95b1ad75f576cffc641853b750");
+
+        let mut big = b"1000:".to_vec();
+        big.extend_from_slice(&[b'x'; 1000]);
+        assert!(ImmutableItem::new(&big).is_err());
+        assert!(ImmutableItem::new(b"3:abcXX").is_err());
+        assert!(MutableItem::sign(&TestSigner, &[0u8; 65], 1, b"i1e").is_err());
+
+        let item = MutableItem::sign(&TestSigner, b"salt", 4, b"l1:a1:be").unwrap();
+        assert!(item.verify(&TestSigner));
+        let forged = MutableItem::new(*item.get_public_key(), b"salt", 5, b"l1:a1:be", *item.get_signature()).unwrap();
+        assert!(!forged.verify(&TestSigner));
+
+        let put = Query::Put { id: NodeId::new([1u8; 20]), token: b"tok".to_vec(), item: Item::Mutable(item.clone()), cas: Some(3) };
+        let message = Message::query(b"aa", put.clone());
+        let buf = message.to_bencode();
+        assert!(buf.windows(12).any(|w| w == b"1:vl1:a1:bee"));
+        assert_eq!(Message::from_bencode(&buf).unwrap().get_query(), Some(&put));
+
+        let get = Message::query(b"ab", Query::Get { id: NodeId::new([1u8; 20]), target: item.get_target(), seq: Some(2) });
+        assert_eq!(Message::from_bencode(&get.to_bencode()).unwrap(), get);
+
+        let mut response = Response::new(NodeId::new([2u8; 20]));
+        response.set_item(&Item::Mutable(item.clone()));
+        let decoded = Message::from_bencode(&Message::response(b"ab", response).to_bencode()).unwrap();
+        let found = decoded.get_response().unwrap().get_item(b"salt").unwrap().unwrap();
+        assert_eq!(found, Item::Mutable(item));
+
+        assert!(ImmutableItem::new(b"d1:ai1e1:ai2ee").is_err());
+        let mut response = Response::new(NodeId::new([2u8; 20]));
+        response.set_token(b"tk");
+        response.set_item(&found);
+        let keys: Vec<Vec<u8>> = BencodeObject::from(&response).iter().map(|(k, _)| k.as_bytes().to_vec()).collect();
+        let mut sorted = keys.clone();
+        sorted.sort();
+        assert_eq!(keys, sorted);
+        assert_eq!(keys.len(), 6);
+        println!("DHT items passed.");
+    }
+
+    #[test]
+    fn item_store() {
+        let clock = Arc::new(ManualClock::new());
+        let mut store = ItemStore::new(clock.clone());
+        store.set_lifetime(Duration::from_secs(60));
+
+        let immutable = Item::Immutable(ImmutableItem::new(b"12:Hello World!").unwrap());
+        store.put(immutable.clone(), None, &TestSigner).unwrap();
+        assert_eq!(store.get(&immutable.get_target()), Some(&immutable));
+
+        let v1 = MutableItem::sign(&TestSigner, b"", 1, b"i1e").unwrap();
+        let v2 = MutableItem::sign(&TestSigner, b"", 2, b"i2e").unwrap();
+        store.put(Item::Mutable(v2.clone()), None, &TestSigner).unwrap();
+        let error = store.put(Item::Mutable(v1.clone()), None, &TestSigner).unwrap_err();
+        assert_eq!(error.get_code().code(), 302);
+
+        let v3 = MutableItem::sign(&TestSigner, b"", 3, b"i3e").unwrap();
+        assert_eq!(store.put(Item::Mutable(v3.clone()), Some(1), &TestSigner).unwrap_err().get_code(), ErrorCode::CasMismatch);
+        store.put(Item::Mutable(v3.clone()), Some(2), &TestSigner).unwrap();
+
+        let rival = MutableItem::sign(&TestSigner, b"", 3, b"i4e").unwrap();
+        assert_eq!(store.put(Item::Mutable(rival), None, &TestSigner).unwrap_err().get_code().code(), 302);
+        assert_eq!(store.get(&v3.get_target()), Some(&Item::Mutable(v3.clone())));
+
+        let forged = MutableItem::new(*v3.get_public_key(), b"", 9, b"i3e", *v3.get_signature()).unwrap();
+        assert_eq!(store.put(Item::Mutable(forged), None, &TestSigner).unwrap_err().get_code(), ErrorCode::InvalidSignature);
+
+        let mut response = Response::new(NodeId::new([0u8; 20]));
+        assert!(store.fill_response(&v3.get_target(), Some(3), &mut response));
+        assert!(!response.has_item() && response.get_seq() == Some(3));
+        let mut response = Response::new(NodeId::new([0u8; 20]));
+        store.fill_response(&v3.get_target(), Some(2), &mut response);
+        assert_eq!(response.get_item(b"").unwrap(), Some(Item::Mutable(v3.clone())));
+
+        clock.advance(Duration::from_secs(30));
+        store.put(Item::Mutable(v3.clone()), None, &TestSigner).unwrap();
+        clock.advance(Duration::from_secs(31));
+        assert_eq!(store.get(&immutable.get_target()), None);
+        assert_eq!(store.expire(), 1);
+        assert_eq!(store.len(), 1);
+        assert_eq!(clock.now(), Duration::from_secs(61));
+
+        store.set_max_items(2);
+        let a = Item::Immutable(ImmutableItem::new(b"1:a").unwrap());
+        let b = Item::Immutable(ImmutableItem::new(b"1:b").unwrap());
+        store.put(a.clone(), None, &TestSigner).unwrap();
+        clock.advance(Duration::from_secs(1));
+        store.put(b.clone(), None, &TestSigner).unwrap();
+        assert_eq!(store.len(), 2);
+        assert!(store.get(&v3.get_target()).is_none());
+        assert!(store.get(&a.get_target()).is_some());
+        println!("Item store passed.");
+    }
+
+    #[test]
+    fn item_store_verbatim() {
+        let clock = Arc::new(ManualClock::new());
+        let mut store = ItemStore::new(clock);
+        let value = b"d1:bi1e1:ad1:zi0e1:yi0eee";
+
+        let immutable = Item::Immutable(ImmutableItem::new(value).unwrap());
+        let mutable = MutableItem::sign(&TestSigner, b"salt", 1, value).unwrap();
+        let id = NodeId::new([3u8; 20]);
+
+        let put = Message::query(b"aa", Query::Put { id, token: vec![1, 2], item: Item::Mutable(mutable.clone()), cas: None });
+        let put = Message::from_bencode(&put.to_bencode()).unwrap();
+        let item = match put.get_query() {
+            Some(Query::Put { item, .. }) => item.clone(),
+            other => panic!("Unexpected query {:?}", other)
+        };
+        assert_eq!(item.get_value().as_slice(), value);
+
+        store.put(immutable.clone(), None, &TestSigner).unwrap();
+        store.put(item, None, &TestSigner).unwrap();
+
+        for (target, salt) in [(immutable.get_target(), &b""[..]), (mutable.get_target(), &b"salt"[..])] {
+            let mut response = Response::new(id);
+            assert!(store.fill_response(&target, None, &mut response));
+            let wire = Message::response(b"aa", response).to_bencode();
+            let decoded = Message::from_bencode(&wire).unwrap();
+            let item = decoded.get_response().unwrap().get_item(salt).unwrap().unwrap();
+            assert_eq!(item.get_value().as_slice(), value);
+            assert_eq!(item.get_target(), target);
+
+            if let Item::Mutable(item) = item {
+                assert!(item.verify(&TestSigner));
+            }
+        }
+        println!("Item store verbatim passed.");
+    }
+
+    #[test]
+    fn token_manager() {
+        let clock = Arc::new(ManualClock::new());
+        let mut tokens = TokenManager::with_seed(clock.clone(), 42);
+        tokens.set_interval(Duration::from_secs(300));
+
+        let v4: std::net::IpAddr = "10.0.0.1".parse().unwrap();
+        let v6: std::net::IpAddr = "2001:db8::1".parse().unwrap();
+        let token = tokens.generate(&v4);
+        let token6 = tokens.generate(&v6);
+        assert_ne!(token, token6);
+        assert!(tokens.validate(&v4, &token));
+        assert!(tokens.validate(&v6, &token6));
+        assert!(!tokens.validate(&v6, &token));
+        assert!(!tokens.validate(&"10.0.0.2".parse().unwrap(), &token));
+
+        clock.advance(Duration::from_secs(300));
+        assert_ne!(tokens.generate(&v4), token);
+        assert!(tokens.validate(&v4, &token));
+
+        clock.advance(Duration::from_secs(300));
+        assert!(!tokens.validate(&v4, &token));
+        assert_eq!(tokens.check(&v4, &token).unwrap_err().get_code(), ErrorCode::Protocol);
+
+        let fresh = tokens.generate(&v4);
+        clock.advance(Duration::from_secs(900));
+        assert!(tokens.check(&v4, &fresh).is_err());
+        println!("Token manager passed.");
+    }
+
+    #[test]
+    fn routing_table() {
+        let clock = Arc::new(ManualClock::new());
+        let mut table = RoutingTable::new(NodeId::new([0u8; 20]), clock.clone());
+        let node = |first: u8, last: u8, port: u16| {
+            let mut id = [0u8; 20];
+            id[0] = first;
+            id[19] = last;
+            CompactNode::new(NodeId::new(id), std::net::SocketAddr::from(([10, 0, first, last], port)))
+        };
+
+        for i in 0..8 {
+            assert_eq!(table.heard_from(node(0x80, i, 1)), InsertResult::Added);
+        }
+        assert_eq!(table.heard_from(node(0x80, 8, 1)), InsertResult::Replacement);
+        assert_eq!(table.get_bucket_count(), 2);
+        assert_eq!(table.heard_from(node(0x01, 1, 1)), InsertResult::Added);
+        assert_eq!(table.heard_from(node(0x80, 0, 2)), InsertResult::Ignored);
+        assert_eq!(table.heard_from(node(0x80, 0, 1)), InsertResult::Updated);
+        assert_eq!(table.len(), 9);
+
+        let bad = *node(0x80, 3, 1).get_id();
+        for _ in 0..3 {
+            table.failed(&bad);
+        }
+        assert!(table.get_node(&bad).is_none());
+        assert!(table.get_node(node(0x80, 8, 1).get_id()).is_some());
+
+        let closest = table.find_closest(&NodeId::new([0u8; 20]), 3);
+        assert_eq!(closest[0], node(0x01, 1, 1));
+        assert_eq!(closest.len(), 3);
+
+        clock.advance(Duration::from_secs(16 * 60));
+        assert_eq!(table.get_node(node(0x01, 1, 1).get_id()).unwrap().get_state(clock.now()), NodeState::Questionable);
+        assert_eq!(table.get_questionable().len(), 9);
+
+        let moved = node(0x01, 1, 9);
+        assert_eq!(table.seen(moved), InsertResult::Ignored);
+        assert_eq!(table.get_node(moved.get_id()).unwrap().get_node(), &node(0x01, 1, 1));
+        assert_eq!(table.heard_from(moved), InsertResult::Updated);
+        assert_eq!(table.get_node(moved.get_id()).unwrap().get_node(), &moved);
+        assert_eq!(table.heard_from(node(0x01, 1, 1)), InsertResult::Ignored);
+        clock.advance(Duration::from_secs(16 * 60));
+        assert_eq!(table.seen(node(0x80, 9, 1)), InsertResult::Replacement);
+
+        let path = temp_dir("routing_table").join("dht.dat");
+        table.save(&path).unwrap();
+        let loaded = RoutingTable::load(&path, clock.clone()).unwrap();
+        assert_eq!(loaded.get_id(), table.get_id());
+        assert_eq!(loaded.len(), 9);
+        assert_eq!(loaded.get_timestamp(), Some(clock.now().as_secs()));
+        assert_eq!(loaded.get_replacement_count(), table.get_replacement_count());
+        assert_eq!(loaded.get_bucket_count(), table.get_bucket_count());
+
+        let mut full = RoutingTable::new(NodeId::new([0u8; 20]), clock.clone());
+        for i in 0..10 {
+            full.heard_from(node(0x80, i, 1));
+        }
+        for _ in 0..3 {
+            full.failed(node(0x80, 0, 1).get_id());
+        }
+        full.failed(node(0x80, 2, 1).get_id());
+        assert_eq!((full.len(), full.get_replacement_count()), (8, 1));
+
+        let mut ben = full.to_object();
+        let nodes = ben.get::<BencodeBytes>("nodes").unwrap().as_bytes()[26..].to_vec();
+        let failures = ben.get::<BencodeBytes>("nodes.f").unwrap().as_bytes()[1..].to_vec();
+        ben.put("nodes", nodes);
+        ben.put("nodes.f", failures);
+        let loaded = RoutingTable::from_object(&ben, clock.clone()).unwrap();
+        assert_eq!((loaded.len(), loaded.get_replacement_count()), (7, 1));
+        assert_eq!(loaded.get_node(node(0x80, 2, 1).get_id()).unwrap().get_failures(), 1);
+
+        table.failed(node(0x01, 1, 9).get_id());
+        let loaded = RoutingTable::from_object(&table.to_object(), clock.clone()).unwrap();
+        assert_eq!(loaded.get_node(node(0x01, 1, 9).get_id()).unwrap().get_failures(), 1);
+        assert_eq!(loaded.to_object(), table.to_object());
+        println!("Routing table passed.");
+    }
+
+    #[test]
+    fn transaction_manager() {
+        let clock = Arc::new(ManualClock::new());
+        let mut transactions = TransactionManager::new(clock.clone());
+        transactions.set_timeout(Duration::from_secs(2));
+        transactions.set_max_retries(1);
+
+        let peer: std::net::SocketAddr = "10.0.0.1:6881".parse().unwrap();
+        let id = NodeId::new([1u8; 20]);
+        let a = transactions.send(Query::Ping { id }, peer).unwrap();
+        let b = transactions.send(Query::FindNode { id, target: id }, peer).unwrap();
+        assert_ne!(a.get_transaction_id(), b.get_transaction_id());
+        assert_eq!(transactions.len(), 2);
+
+        let reply = Message::from_bencode(&Message::response(a.get_transaction_id(), Response::new(id)).to_bencode()).unwrap();
+        let other: std::net::SocketAddr = "10.0.0.2:6881".parse().unwrap();
+        assert_eq!(transactions.receive(&reply, &other).unwrap_err().kind(), std::io::ErrorKind::PermissionDenied);
+        assert_eq!(transactions.receive(&reply, &peer).unwrap().get_query().get_method(), "ping");
+        assert_eq!(transactions.receive(&reply, &peer).unwrap_err().kind(), std::io::ErrorKind::NotFound);
+        assert!(transactions.receive(&a, &peer).is_err());
+
+        clock.advance(Duration::from_secs(2));
+        match transactions.poll_timeouts().as_slice() {
+            [TimeoutAction::Retry(message, address)] => {
+                assert_eq!(message, &b);
+                assert_eq!(address, &peer);
+            }
+            other => panic!("Unexpected actions {:?}", other)
+        }
+
+        clock.advance(Duration::from_secs(1));
+        assert!(transactions.poll_timeouts().is_empty());
+        clock.advance(Duration::from_secs(1));
+        match transactions.poll_timeouts().as_slice() {
+            [TimeoutAction::Expired(transaction)] => assert_eq!(transaction.get_attempts(), 2),
+            other => panic!("Unexpected actions {:?}", other)
+        }
+
+        let error = Message::error(b.get_transaction_id(), KrpcError::from(ErrorCode::Generic));
+        assert!(transactions.receive(&error, &peer).is_err());
+        let c = transactions.send(Query::Ping { id }, peer).unwrap();
+        let error = Message::error(c.get_transaction_id(), KrpcError::from(ErrorCode::Generic));
+        assert!(transactions.receive(&error, &peer).is_ok());
+        assert!(transactions.is_empty());
+        println!("Transaction manager passed.");
+    }
+
+    #[test]
+    fn dht_node() {
+        let clock = Arc::new(ManualClock::new());
+        let mut store = PeerStore::new(clock.clone());
+        let peer: std::net::SocketAddr = "10.0.0.1:6881".parse().unwrap();
+        store.announce([7u8; 20], peer);
+        store.announce([7u8; 20], peer);
+        assert_eq!(store.get_peers(&[7u8; 20]), vec![peer]);
+        clock.advance(Duration::from_secs(31 * 60));
+        assert!(store.get_peers(&[7u8; 20]).is_empty());
+        store.expire();
+        assert!(store.is_empty());
+
+        let mut nodes: Vec<DhtNode> = (1..=3u8)
+            .map(|i| DhtNode::bind("127.0.0.1:0", NodeId::new([i * 0x40; 20])).unwrap())
+            .collect();
+        let seed = nodes[0].local_addr().unwrap();
+
+        let run = |nodes: &mut [DhtNode]| {
+            for _ in 0..10 {
+                for node in nodes.iter_mut() {
+                    node.poll(Duration::from_millis(5)).unwrap();
+                }
+            }
+        };
+
+        nodes[1].bootstrap(&[seed]).unwrap();
+        run(&mut nodes);
+        nodes[2].bootstrap(&[seed]).unwrap();
+        run(&mut nodes);
+
+        assert_eq!(nodes[0].get_handler().get_routing_table().len(), 2);
+        assert_eq!(nodes[2].get_handler().get_routing_table().len(), 2);
+        assert!(!nodes[2].get_handler().is_lookup_active(&NodeId::new([0xc0; 20])));
+
+        let info_hash = [0x99u8; 20];
+        nodes[2].get_peers(info_hash).unwrap();
+        run(&mut nodes);
+        assert!(nodes[2].get_handler().get_found_peers(&info_hash).is_empty());
+        nodes[2].announce(info_hash, 51413, false).unwrap();
+        run(&mut nodes);
+        assert_eq!(nodes[0].get_handler().get_peer_store().get_peers(&info_hash).len(), 1);
+
+        nodes[1].get_peers(info_hash).unwrap();
+        run(&mut nodes);
+        let found = nodes[1].get_handler().get_found_peers(&info_hash);
+        assert_eq!(found, vec!["127.0.0.1:51413".parse::<std::net::SocketAddr>().unwrap()]);
+
+        let before = nodes[0].get_handler().get_routing_table().len();
+        let socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        socket.set_read_timeout(Some(Duration::from_secs(1))).unwrap();
+        socket.send_to(b"d1:t2:xy1:y1:qe", seed).unwrap();
+        nodes[0].poll(Duration::from_millis(5)).unwrap();
+        let mut buf = [0u8; 1500];
+        let (len, _) = socket.recv_from(&mut buf).unwrap();
+        let reply = Message::from_bencode(&buf[..len]).unwrap();
+        assert_eq!(reply.get_transaction_id(), b"xy");
+        assert_eq!(reply.get_error().unwrap().get_code(), ErrorCode::Protocol);
+        assert_eq!(nodes[0].get_handler().get_routing_table().len(), before);
+
+        let mut table = RoutingTable::new(NodeId::new([0u8; 20]), clock.clone());
+        for i in 1..=10u8 {
+            table.heard_from(CompactNode::new(NodeId::new([i; 20]), std::net::SocketAddr::from(([10, 0, 1, i], 6881))));
+        }
+        let mut handler = DhtHandler::with_routing_table(table, clock.clone());
+        let info_hash = [1u8; 20];
+        let out = handler.get_peers(info_hash);
+        let mut first: Vec<u8> = out.iter().map(|(_, a)| match a.ip() { std::net::IpAddr::V4(ip) => ip.octets()[3], _ => 0 }).collect();
+        first.sort();
+        assert_eq!(out.len(), LOOKUP_ALPHA);
+        assert_eq!(first, vec![1, 2, 3]);
+        assert!(handler.get_peers(info_hash).is_empty());
+        assert!(handler.is_lookup_active(&NodeId::from(info_hash)));
+
+        let (buf, to) = out.iter().find(|(_, a)| *a == std::net::SocketAddr::from(([10, 0, 1, 2], 6881))).unwrap().clone();
+        let tid = Message::from_bencode(&buf).unwrap().get_transaction_id().clone();
+        let mut closer = [1u8; 20];
+        closer[19] = 0;
+        let mut response = Response::new(NodeId::new([2u8; 20]));
+        response.set_token(b"tk");
+        response.add_node(CompactNode::new(NodeId::new(closer), std::net::SocketAddr::from(([10, 0, 2, 1], 6881))));
+        let out = handler.handle_packet(&Message::response(&tid, response).to_bencode(), to);
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].1, std::net::SocketAddr::from(([10, 0, 2, 1], 6881)));
+
+        for _ in 0..40 {
+            clock.advance(Duration::from_secs(6));
+            handler.tick();
+        }
+        assert!(!handler.is_lookup_active(&NodeId::from(info_hash)));
+        assert_eq!(handler.announce(info_hash, 51413, false).len(), 1);
+
+        clock.advance(LOOKUP_RESULT_LIFETIME);
+        handler.tick();
+        assert!(handler.announce(info_hash, 51413, false).is_empty());
+
+        let shared = std::net::SocketAddr::from(([10, 0, 3, 1], 6881));
+        let mut table = RoutingTable::new(NodeId::new([0u8; 20]), clock.clone());
+        table.heard_from(CompactNode::new(NodeId::new([3u8; 20]), shared));
+        table.heard_from(CompactNode::new(NodeId::new([4u8; 20]), shared));
+        let mut handler = DhtHandler::with_routing_table(table, clock.clone());
+
+        let out = handler.find_node(NodeId::new([4u8; 20]));
+        assert_eq!(out.len(), 1);
+        let tid = Message::from_bencode(&out[0].0).unwrap().get_transaction_id().clone();
+        let mut malformed = BencodeObject::new();
+        malformed.put("t", tid);
+        malformed.put("y", "r");
+        assert!(handler.handle_packet(&malformed.to_bencode(), shared).is_empty());
+        assert!(handler.get_transactions_mut().is_empty());
+        assert_eq!(handler.get_routing_table().get_node(&NodeId::new([4u8; 20])).unwrap().get_failures(), 1);
+
+        handler.find_node(NodeId::new([4u8; 20]));
+        for _ in 0..2 {
+            clock.advance(Duration::from_secs(6));
+            handler.tick();
+        }
+        assert_eq!(handler.get_routing_table().get_node(&NodeId::new([4u8; 20])).unwrap().get_failures(), 2);
+        assert_eq!(handler.get_routing_table().get_node(&NodeId::new([3u8; 20])).unwrap().get_failures(), 0);
+        println!("DHT node passed.");
+    }
+
+    #[test]
+    fn dht_simulator() {
+        let simulate = |seed: u64| {
+            let mut sim = NetworkSimulator::new(seed);
+            sim.set_latency(Duration::from_millis(10), Duration::from_millis(80));
+            sim.set_loss(0.05);
+
+            for i in 0..200 {
+                sim.add_node(match i % 20 {
+                    7 => Behavior::Silent,
+                    11 => Behavior::Garbage,
+                    13 => Behavior::Liar,
+                    _ => Behavior::Honest
+                });
+            }
+            let nat = sim.add_nat_node();
+
+            let seed_node = sim.get_address(0);
+            for node in 1..sim.len() {
+                sim.bootstrap(node, &[seed_node]);
+                sim.run_for(Duration::from_millis(200));
+            }
+            sim.run_for(Duration::from_secs(30));
+
+            let info_hash = [0x42u8; 20];
+            sim.with_handler(5, |handler| handler.get_peers(info_hash));
+            sim.run_for(Duration::from_secs(30));
+            sim.with_handler(5, |handler| handler.announce(info_hash, 51413, false));
+            sim.run_for(Duration::from_secs(30));
+
+            sim.with_handler(150, |handler| handler.get_peers(info_hash));
+            sim.run_for(Duration::from_secs(30));
+            (sim, nat, info_hash)
+        };
+
+        let (sim, nat, info_hash) = simulate(1);
+        assert!(sim.get_dropped() > 0);
+        assert!(sim.get_delivered() > sim.get_dropped());
+
+        let honest: Vec<usize> = (0..200).filter(|i| sim.get_behavior(*i) == Behavior::Honest).collect();
+        let average = honest.iter().map(|i| sim.get_handler(*i).get_routing_table().len()).sum::<usize>() / honest.len();
+        assert!(average >= 16, "average routing table size {}", average);
+        assert!(!sim.get_handler(nat).get_routing_table().is_empty());
+
+        let announced = std::net::SocketAddr::new(sim.get_address(5).ip(), 51413);
+        assert!(sim.get_handler(150).get_found_peers(&info_hash).contains(&announced));
+
+        let (again, _, _) = simulate(1);
+        assert_eq!(again.get_sent(), sim.get_sent());
+        assert_eq!(again.get_dropped(), sim.get_dropped());
+        assert_eq!(again.now(), sim.now());
+        assert_eq!(again.get_delivered(), sim.get_delivered());
+        assert_eq!(again.get_trace(), sim.get_trace());
+        assert_ne!(simulate(2).0.get_trace(), sim.get_trace());
+        println!("DHT simulator passed.");
+    }
+
+    #[test]
+    fn secure_node_id_check() {
+        assert_eq!(crc32c::checksum(b"123456789"), 0xe3069283);
+
+        let vectors = [
+            ("124.31.75.21", "5fbfbff10c5d6a4ec8a88e4c6ab4c28b95eee401"),
+            ("21.75.31.124", "5a3ce9c14e7a08645677bbd1cfe7d8f956d53256"),
+            ("65.23.51.170", "a5d43220bc8f112a3d426c84764f8c2a1150e616"),
+            ("84.124.73.14", "1b0321dd1bb1fe518101ceef99462b947a01ff41"),
+            ("43.213.53.83", "e56f6cbf5b7c4be0237986d5243b87aa6d51305a")
+        ];
+
+        for (ip, expected) in vectors {
+            let ip: std::net::IpAddr = ip.parse().unwrap();
+            let expected = NodeId::from_hex(expected).unwrap();
+            assert!(is_secure_node_id(&expected, &ip));
+
+            let mut bytes = *expected.as_bytes();
+            bytes[0] = 0;
+            bytes[1] = 0;
+            bytes[2] &= 0x07;
+            let id = NodeId::new(bytes);
+            assert!(!is_secure_node_id(&id, &ip));
+            assert_eq!(secure_node_id(&ip, &id), expected);
+        }
+
+        let ip: std::net::IpAddr = "2001:db8::1".parse().unwrap();
+        let id = random_secure_node_id(&ip);
+        assert!(is_secure_node_id(&id, &ip));
+        assert!(!is_secure_node_id(&id, &"2001:db9::1".parse().unwrap()));
+
+        for exempt in ["10.1.2.3", "172.16.0.1", "192.168.1.1", "169.254.0.1", "127.0.0.1", "::1", "fd00::1", "fe80::1"] {
+            assert!(is_exempt_address(&exempt.parse().unwrap()));
+        }
+        assert!(!is_exempt_address(&"8.8.8.8".parse().unwrap()));
+        assert!(is_secure_node_id(&NodeId::new([0u8; 20]), &"10.0.0.1".parse().unwrap()));
+
+        let mut message = Message::query(b"aa", Query::Ping { id });
+        let address: std::net::SocketAddr = "124.31.75.21:6881".parse().unwrap();
+        message.set_ip_address(&address);
+        let decoded = Message::from_bencode(&message.to_bencode()).unwrap();
+        assert_eq!(decoded.get_ip_address().unwrap(), Some(address));
+        message.set_ip(&[1, 2, 3]);
+        assert!(message.get_ip_address().is_err());
+
+        let clock = Arc::new(ManualClock::new());
+        let local = NodeId::new([1u8; 20]);
+        let mut handler = DhtHandler::new(local, clock.clone());
+        handler.set_enforce_secure_ids(true);
+        let requester: std::net::SocketAddr = "21.75.31.124:6881".parse().unwrap();
+        let query = Message::query(b"aa", Query::Ping { id: NodeId::new([9u8; 20]) });
+        let out = handler.handle_packet(&query.to_bencode(), requester);
+        let reply = Message::from_bencode(&out[0].0).unwrap();
+        assert_eq!(reply.get_ip_address().unwrap(), Some(requester));
+        assert!(handler.get_routing_table().is_empty());
+
+        let secure = random_secure_node_id(&requester.ip());
+        let query = Message::query(b"ab", Query::Ping { id: secure });
+        handler.handle_packet(&query.to_bencode(), requester);
+        assert_eq!(handler.get_routing_table().len(), 1);
+
+        let respond = |handler: &mut DhtHandler, from: std::net::SocketAddr, claimed: std::net::SocketAddr| {
+            let out = handler.ping(from);
+            let tid = Message::from_bencode(&out[0].0).unwrap().get_transaction_id().clone();
+            let mut response = Message::response(&tid, Response::new(random_secure_node_id(&from.ip())));
+            response.set_ip_address(&claimed);
+            handler.handle_packet(&response.to_bencode(), from);
+        };
+
+        respond(&mut handler, requester, address);
+        respond(&mut handler, requester, address);
+        respond(&mut handler, "192.168.1.5:6881".parse().unwrap(), address);
+        respond(&mut handler, "31.0.0.2:6881".parse().unwrap(), "10.0.0.2:6881".parse().unwrap());
+        assert_eq!(handler.get_external_address(), None);
+        respond(&mut handler, "31.0.0.3:6881".parse().unwrap(), address);
+        assert_eq!(handler.get_external_address(), None);
+        respond(&mut handler, "31.0.0.4:6881".parse().unwrap(), address);
+        assert_eq!(handler.get_external_address(), Some(&address));
+        println!("Secure node id passed.");
+    }
 }
@@ -0,0 +1,252 @@
+use std::{fmt, io};
+use std::fmt::Formatter;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+use crate::metainfo::info_hash::InfoHash;
+use crate::metainfo::torrent::Torrent;
+use crate::utils::{base32, hex, url_encoding};
+
+pub const MAGNET_PREFIX: &str = "magnet:?";
+const MULTIHASH_SHA256: [u8; 2] = [0x12, 0x20];
+
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct MagnetUri {
+    info_hash_v1: Option<InfoHash>,
+    info_hash_v2: Option<InfoHash>,
+    display_name: Option<String>,
+    exact_length: Option<u64>,
+    trackers: Vec<String>,
+    web_seeds: Vec<String>,
+    peers: Vec<String>,
+    select_only: Vec<RangeInclusive<usize>>
+}
+
+impl MagnetUri {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_info_hash(info_hash: InfoHash) -> Self {
+        let mut magnet = Self::new();
+        magnet.set_info_hash(info_hash);
+        magnet
+    }
+
+    pub fn from_torrent(torrent: &Torrent) -> Self {
+        let info = torrent.get_info();
+
+        let mut magnet = Self::new();
+        magnet.info_hash_v1 = info.get_info_hash_v1();
+        magnet.info_hash_v2 = info.get_info_hash_v2();
+        magnet.display_name = Some(info.get_name());
+        magnet.trackers = torrent.get_trackers().iter().map(|u| u.to_string()).collect();
+        magnet.web_seeds = torrent.get_url_list().get_urls().clone();
+        magnet
+    }
+
+    pub fn get_info_hash_v1(&self) -> Option<&InfoHash> {
+        self.info_hash_v1.as_ref()
+    }
+
+    pub fn get_info_hash_v2(&self) -> Option<&InfoHash> {
+        self.info_hash_v2.as_ref()
+    }
+
+    pub fn get_info_hash(&self) -> Option<&InfoHash> {
+        self.info_hash_v1.as_ref().or(self.info_hash_v2.as_ref())
+    }
+
+    pub fn set_info_hash(&mut self, info_hash: InfoHash) {
+        match info_hash {
+            InfoHash::V1(_) => self.info_hash_v1 = Some(info_hash),
+            InfoHash::V2(_) => self.info_hash_v2 = Some(info_hash)
+        }
+    }
+
+    pub fn get_display_name(&self) -> Option<&String> {
+        self.display_name.as_ref()
+    }
+
+    pub fn set_display_name(&mut self, display_name: &str) {
+        self.display_name = Some(display_name.to_string());
+    }
+
+    pub fn get_exact_length(&self) -> Option<u64> {
+        self.exact_length
+    }
+
+    pub fn set_exact_length(&mut self, exact_length: u64) {
+        self.exact_length = Some(exact_length);
+    }
+
+    pub fn get_trackers(&self) -> &Vec<String> {
+        &self.trackers
+    }
+
+    pub fn add_tracker(&mut self, url: &str) {
+        if !self.trackers.iter().any(|t| t == url) {
+            self.trackers.push(url.to_string());
+        }
+    }
+
+    pub fn get_web_seeds(&self) -> &Vec<String> {
+        &self.web_seeds
+    }
+
+    pub fn add_web_seed(&mut self, url: &str) {
+        if !self.web_seeds.iter().any(|w| w == url) {
+            self.web_seeds.push(url.to_string());
+        }
+    }
+
+    pub fn get_peers(&self) -> &Vec<String> {
+        &self.peers
+    }
+
+    pub fn add_peer(&mut self, peer: &str) {
+        self.peers.push(peer.to_string());
+    }
+
+    pub fn get_select_only(&self) -> &Vec<RangeInclusive<usize>> {
+        &self.select_only
+    }
+
+    pub fn add_select_only(&mut self, range: RangeInclusive<usize>) {
+        self.select_only.push(range);
+    }
+
+    pub fn is_selected(&self, file: usize) -> bool {
+        self.select_only.is_empty() || self.select_only.iter().any(|r| r.contains(&file))
+    }
+}
+
+fn parse_exact_topic(value: &str) -> io::Result<InfoHash> {
+    if let Some(hash) = value.strip_prefix("urn:btih:") {
+        return match hash.len() {
+            40 => InfoHash::from_hex(hash),
+            32 => InfoHash::from_bytes(&base32::decode(hash)?),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidInput, "btih must be 40 hex or 32 base32 characters"))
+        };
+    }
+
+    if let Some(hash) = value.strip_prefix("urn:btmh:") {
+        let multihash = hex::decode(hash)?;
+        if multihash.len() != 34 || multihash[..2] != MULTIHASH_SHA256 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "btmh must be a SHA-256 multihash"));
+        }
+        return InfoHash::from_bytes(&multihash[2..]);
+    }
+
+    Err(io::Error::new(io::ErrorKind::InvalidInput, "Unsupported exact topic"))
+}
+
+fn parse_select_only(value: &str) -> io::Result<Vec<RangeInclusive<usize>>> {
+    value.split(',').filter(|s| !s.is_empty()).map(|part| {
+        let parse = |s: &str| s.trim().parse::<usize>()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e));
+
+        match part.split_once('-') {
+            Some((start, end)) => {
+                let (start, end) = (parse(start)?, parse(end)?);
+                if start > end {
+                    return Err(io::Error::new(io::ErrorKind::InvalidInput, "Invalid select-only range"));
+                }
+                Ok(start..=end)
+            }
+            None => {
+                let index = parse(part)?;
+                Ok(index..=index)
+            }
+        }
+    }).collect()
+}
+
+impl FromStr for MagnetUri {
+
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> io::Result<Self> {
+        let query = s.strip_prefix(MAGNET_PREFIX)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Not a magnet URI"))?;
+
+        let mut magnet = Self::new();
+
+        for pair in query.split('&').filter(|p| !p.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let value = String::from_utf8(url_encoding::decode(value)?)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+            let key = match key {
+                "x.pe" => key,
+                _ => key.split('.').next().unwrap_or(key)
+            };
+
+            match key {
+                "xt" => {
+                    if let Ok(hash) = parse_exact_topic(&value) {
+                        magnet.set_info_hash(hash);
+                    }
+                }
+                "dn" => magnet.display_name = Some(value),
+                "xl" => magnet.exact_length = value.parse::<u64>().ok(),
+                "tr" => magnet.add_tracker(&value),
+                "ws" => magnet.add_web_seed(&value),
+                "x.pe" => magnet.add_peer(&value),
+                "so" => magnet.select_only.extend(parse_select_only(&value)?),
+                _ => {}
+            }
+        }
+
+        if magnet.info_hash_v1.is_none() && magnet.info_hash_v2.is_none() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Magnet URI has no BitTorrent info hash"));
+        }
+
+        Ok(magnet)
+    }
+}
+
+impl fmt::Display for MagnetUri {
+
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut params = Vec::new();
+
+        if let Some(hash) = &self.info_hash_v1 {
+            params.push(format!("xt=urn:btih:{}", hash.to_hex()));
+        }
+
+        if let Some(hash) = &self.info_hash_v2 {
+            params.push(format!("xt=urn:btmh:{}{}", hex::encode(&MULTIHASH_SHA256), hash.to_hex()));
+        }
+
+        if let Some(name) = &self.display_name {
+            params.push(format!("dn={}", url_encoding::encode(name.as_bytes())));
+        }
+
+        if let Some(length) = self.exact_length {
+            params.push(format!("xl={}", length));
+        }
+
+        for tracker in &self.trackers {
+            params.push(format!("tr={}", url_encoding::encode(tracker.as_bytes())));
+        }
+
+        for seed in &self.web_seeds {
+            params.push(format!("ws={}", url_encoding::encode(seed.as_bytes())));
+        }
+
+        for peer in &self.peers {
+            params.push(format!("x.pe={}", url_encoding::encode(peer.as_bytes())));
+        }
+
+        if !self.select_only.is_empty() {
+            let ranges: Vec<String> = self.select_only.iter().map(|r| match r.start() == r.end() {
+                true => r.start().to_string(),
+                false => format!("{}-{}", r.start(), r.end())
+            }).collect();
+            params.push(format!("so={}", ranges.join(",")));
+        }
+
+        write!(f, "{}{}", MAGNET_PREFIX, params.join("&"))
+    }
+}
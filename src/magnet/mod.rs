@@ -0,0 +1 @@
+pub mod magnet_uri;
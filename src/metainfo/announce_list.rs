@@ -0,0 +1,187 @@
+use std::io;
+use crate::utils::random::Random;
+use crate::variables::bencode_array::{AddArray, BencodeArray};
+use crate::variables::bencode_bytes::BencodeBytes;
+use crate::variables::inter::bencode_variable::{FromBencode, ToBencode};
+
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct AnnounceList {
+    tiers: Vec<Vec<Vec<u8>>>
+}
+
+impl AnnounceList {
+
+    pub fn new() -> Self {
+        Self {
+            tiers: Vec::new()
+        }
+    }
+
+    pub fn from_tiers(tiers: Vec<Vec<String>>) -> Self {
+        Self {
+            tiers: tiers.into_iter().map(|t| t.into_iter().map(String::into_bytes).collect()).collect()
+        }
+    }
+
+    pub fn get_tiers(&self) -> &Vec<Vec<Vec<u8>>> {
+        &self.tiers
+    }
+
+    pub fn get_tier(&self, tier: usize) -> Option<&Vec<Vec<u8>>> {
+        self.tiers.get(tier)
+    }
+
+    pub fn len(&self) -> usize {
+        self.tiers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiers.is_empty()
+    }
+
+    pub fn get_tracker_count(&self) -> usize {
+        self.tiers.iter().map(|t| t.len()).sum()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.tiers.iter().flatten().filter_map(|u| std::str::from_utf8(u).ok())
+    }
+
+    pub fn get_first(&self) -> Option<&str> {
+        self.iter().next()
+    }
+
+    pub fn contains(&self, url: &str) -> bool {
+        self.tiers.iter().flatten().any(|u| u.as_slice() == url.as_bytes())
+    }
+
+    pub fn add_tier(&mut self, urls: Vec<String>) {
+        let urls = urls.into_iter().fold(Vec::new(), |mut tier, url| {
+            if !self.contains(&url) && !tier.contains(&url) {
+                tier.push(url);
+            }
+            tier
+        });
+
+        if !urls.is_empty() {
+            self.tiers.push(urls.into_iter().map(String::into_bytes).collect());
+        }
+    }
+
+    pub fn add_tracker(&mut self, tier: usize, url: &str) -> bool {
+        if self.contains(url) {
+            return false;
+        }
+
+        if tier >= self.tiers.len() {
+            self.tiers.push(vec![url.as_bytes().to_vec()]);
+        } else {
+            self.tiers[tier].push(url.as_bytes().to_vec());
+        }
+        true
+    }
+
+    pub fn remove_tracker(&mut self, url: &str) -> bool {
+        let before = self.get_tracker_count();
+        for tier in &mut self.tiers {
+            tier.retain(|u| u.as_slice() != url.as_bytes());
+        }
+        self.tiers.retain(|t| !t.is_empty());
+        before != self.get_tracker_count()
+    }
+
+    pub fn dedup(&mut self) {
+        let mut seen = Vec::new();
+        for tier in &mut self.tiers {
+            tier.retain(|url| {
+                if seen.contains(url) {
+                    return false;
+                }
+                seen.push(url.clone());
+                true
+            });
+        }
+        self.tiers.retain(|t| !t.is_empty());
+    }
+
+    pub fn shuffle(&mut self) {
+        self.shuffle_with(&mut Random::from_time());
+    }
+
+    pub fn shuffle_with_seed(&mut self, seed: u64) {
+        self.shuffle_with(&mut Random::new(seed));
+    }
+
+    fn shuffle_with(&mut self, random: &mut Random) {
+        for tier in &mut self.tiers {
+            random.shuffle(tier);
+        }
+    }
+
+    pub fn promote(&mut self, url: &str) -> bool {
+        for tier in &mut self.tiers {
+            if let Some(i) = tier.iter().position(|u| u.as_slice() == url.as_bytes()) {
+                let url = tier.remove(i);
+                tier.insert(0, url);
+                return true;
+            }
+        }
+        false
+    }
+}
+
+impl TryFrom<&BencodeArray> for AnnounceList {
+
+    type Error = io::Error;
+
+    fn try_from(ben: &BencodeArray) -> io::Result<Self> {
+        let mut tiers = Vec::new();
+
+        for tier in ben.iter() {
+            let tier = tier.as_any().downcast_ref::<BencodeArray>()
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Announce tier must be a list"))?;
+
+            let mut urls = Vec::new();
+            for url in tier.iter() {
+                let url = url.as_any().downcast_ref::<BencodeBytes>()
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Tracker URL must be bytes"))?;
+                urls.push(url.as_bytes().to_vec());
+            }
+            tiers.push(urls);
+        }
+
+        Ok(Self {
+            tiers
+        })
+    }
+}
+
+impl From<&AnnounceList> for BencodeArray {
+
+    fn from(list: &AnnounceList) -> Self {
+        let mut tiers = BencodeArray::new();
+        for tier in &list.tiers {
+            let mut urls = BencodeArray::new();
+            for url in tier {
+                urls.push(url.clone());
+            }
+            tiers.push(urls);
+        }
+        tiers
+    }
+}
+
+impl FromBencode for AnnounceList {
+
+    fn from_bencode_with_offset(buf: &[u8]) -> io::Result<(Self, usize)> {
+        let (ben, off) = BencodeArray::from_bencode_with_offset(buf)?;
+        Ok((Self::try_from(&ben)?, off))
+    }
+}
+
+impl ToBencode for AnnounceList {
+
+    fn to_bencode(&self) -> Vec<u8> {
+        BencodeArray::from(self).to_bencode()
+    }
+}
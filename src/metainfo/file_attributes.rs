@@ -0,0 +1,86 @@
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub struct FileAttributes {
+    padding: bool,
+    executable: bool,
+    hidden: bool,
+    symlink: bool
+}
+
+impl FileAttributes {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn padding() -> Self {
+        Self {
+            padding: true,
+            ..Self::default()
+        }
+    }
+
+    pub fn is_padding(&self) -> bool {
+        self.padding
+    }
+
+    pub fn is_executable(&self) -> bool {
+        self.executable
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        self.hidden
+    }
+
+    pub fn is_symlink(&self) -> bool {
+        self.symlink
+    }
+
+    pub fn set_padding(&mut self, padding: bool) {
+        self.padding = padding;
+    }
+
+    pub fn set_executable(&mut self, executable: bool) {
+        self.executable = executable;
+    }
+
+    pub fn set_hidden(&mut self, hidden: bool) {
+        self.hidden = hidden;
+    }
+
+    pub fn set_symlink(&mut self, symlink: bool) {
+        self.symlink = symlink;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !(self.padding || self.executable || self.hidden || self.symlink)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        if self.padding {
+            buf.push(b'p');
+        }
+        if self.hidden {
+            buf.push(b'h');
+        }
+        if self.executable {
+            buf.push(b'x');
+        }
+        if self.symlink {
+            buf.push(b'l');
+        }
+        buf
+    }
+}
+
+impl From<&[u8]> for FileAttributes {
+
+    fn from(value: &[u8]) -> Self {
+        Self {
+            padding: value.contains(&b'p'),
+            executable: value.contains(&b'x'),
+            hidden: value.contains(&b'h'),
+            symlink: value.contains(&b'l')
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use std::io;
+use crate::metainfo::file_attributes::FileAttributes;
+use crate::variables::bencode_array::{AddArray, BencodeArray};
+use crate::variables::bencode_bytes::BencodeBytes;
+use crate::variables::bencode_number::BencodeNumber;
+use crate::variables::bencode_object::{BencodeObject, GetObject, PutObject};
+use crate::variables::inter::bencode_variable::BencodeVariable;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FileEntry {
+    path: Vec<Vec<u8>>,
+    path_utf8: Option<Vec<Vec<u8>>>,
+    length: u64,
+    attributes: FileAttributes,
+    symlink_path: Option<Vec<Vec<u8>>>,
+    sha1: Option<[u8; 20]>,
+    pieces_root: Option<[u8; 32]>
+}
+
+impl FileEntry {
+
+    pub fn new(path: Vec<Vec<u8>>, length: u64) -> Self {
+        Self {
+            path,
+            path_utf8: None,
+            length,
+            attributes: FileAttributes::new(),
+            symlink_path: None,
+            sha1: None,
+            pieces_root: None
+        }
+    }
+
+    pub fn padding(length: u64) -> Self {
+        let mut entry = Self::new(vec![b".pad".to_vec(), length.to_string().into_bytes()], length);
+        entry.attributes = FileAttributes::padding();
+        entry
+    }
+
+    pub fn get_path(&self) -> &Vec<Vec<u8>> {
+        &self.path
+    }
+
+    pub fn get_path_utf8(&self) -> Option<&Vec<Vec<u8>>> {
+        self.path_utf8.as_ref()
+    }
+
+    pub fn get_path_string(&self) -> String {
+        String::from_utf8_lossy(&self.path.join(&b'/')).into_owned()
+    }
+
+    pub fn get_length(&self) -> u64 {
+        self.length
+    }
+
+    pub fn get_attributes(&self) -> &FileAttributes {
+        &self.attributes
+    }
+
+    pub fn set_attributes(&mut self, attributes: FileAttributes) {
+        self.attributes = attributes;
+    }
+
+    pub fn is_padding(&self) -> bool {
+        self.attributes.is_padding()
+    }
+
+    pub fn get_symlink_path(&self) -> Option<&Vec<Vec<u8>>> {
+        self.symlink_path.as_ref()
+    }
+
+    pub fn set_symlink_path(&mut self, symlink_path: Vec<Vec<u8>>) {
+        self.attributes.set_symlink(true);
+        self.symlink_path = Some(symlink_path);
+    }
+
+    pub fn get_sha1(&self) -> Option<&[u8; 20]> {
+        self.sha1.as_ref()
+    }
+
+    pub fn set_sha1(&mut self, sha1: [u8; 20]) {
+        self.sha1 = Some(sha1);
+    }
+
+    pub fn get_pieces_root(&self) -> Option<&[u8; 32]> {
+        self.pieces_root.as_ref()
+    }
+
+    pub fn set_pieces_root(&mut self, pieces_root: [u8; 32]) {
+        self.pieces_root = Some(pieces_root);
+    }
+
+    pub(crate) fn to_v1_object(&self) -> BencodeObject {
+        let path = path_array(&self.path);
+
+        let mut ben = BencodeObject::new();
+        self.put_attributes(&mut ben);
+        ben.put("length", self.length);
+        ben.put("path", path);
+
+        if let Some(path_utf8) = &self.path_utf8 {
+            ben.put("path.utf-8", path_array(path_utf8));
+        }
+        ben
+    }
+
+    fn put_attributes(&self, ben: &mut BencodeObject) {
+        if !self.attributes.is_empty() {
+            ben.put("attr", self.attributes.to_bytes());
+        }
+
+        if let Some(sha1) = self.sha1 {
+            ben.put("sha1", sha1);
+        }
+
+        if let Some(symlink_path) = &self.symlink_path {
+            ben.put("symlink path", path_array(symlink_path));
+        }
+    }
+
+    pub(crate) fn read_attributes(&mut self, ben: &BencodeObject) -> io::Result<()> {
+        if let Some(attr) = ben.get::<BencodeBytes>("attr") {
+            self.attributes = FileAttributes::from(attr.as_bytes());
+        }
+
+        if let Some(sha1) = ben.get::<BencodeBytes>("sha1") {
+            self.sha1 = Some(sha1.as_bytes().try_into()
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "File sha1 must be 20 bytes"))?);
+        }
+
+        if let Some(list) = ben.get::<BencodeArray>("symlink path") {
+            self.symlink_path = Some(path_list(list)?);
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn to_v2_object(&self) -> BencodeObject {
+        let mut file = BencodeObject::new();
+        self.put_attributes(&mut file);
+        file.put("length", self.length);
+        if let Some(root) = self.pieces_root {
+            file.put("pieces root", root);
+        }
+
+        let mut ben = BencodeObject::new();
+        ben.put("", file);
+        ben
+    }
+}
+
+fn path_array(path: &[Vec<u8>]) -> BencodeArray {
+    let mut list = BencodeArray::new();
+    for component in path {
+        list.push(component);
+    }
+    list
+}
+
+fn path_list(list: &BencodeArray) -> io::Result<Vec<Vec<u8>>> {
+    list.iter().map(|component| {
+        component.as_any().downcast_ref::<BencodeBytes>()
+            .map(|c| c.as_bytes().to_vec())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "File path component must be bytes"))
+    }).collect()
+}
+
+impl TryFrom<&BencodeObject> for FileEntry {
+
+    type Error = io::Error;
+
+    fn try_from(ben: &BencodeObject) -> io::Result<Self> {
+        let length = ben.get::<BencodeNumber>("length")
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "File is missing length"))?
+            .parse::<u64>()?;
+
+        let path = path_list(ben.get::<BencodeArray>("path")
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "File is missing path"))?)?;
+
+        let path_utf8 = match ben.get::<BencodeArray>("path.utf-8") {
+            Some(list) => Some(path_list(list)?),
+            None => None
+        };
+
+        let mut entry = Self {
+            path,
+            path_utf8,
+            length,
+            attributes: FileAttributes::new(),
+            symlink_path: None,
+            sha1: None,
+            pieces_root: None
+        };
+        entry.read_attributes(ben)?;
+        Ok(entry)
+    }
+}
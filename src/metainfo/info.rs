@@ -0,0 +1,263 @@
+use std::io;
+use crate::metainfo::file_entry::FileEntry;
+use crate::metainfo::info_hash::InfoHash;
+use crate::variables::bencode_array::BencodeArray;
+use crate::variables::bencode_bytes::BencodeBytes;
+use crate::variables::bencode_number::BencodeNumber;
+use crate::variables::bencode_object::{BencodeObject, GetObject, ObjectOptions};
+use crate::variables::inter::bencode_variable::{BencodeVariable, FromBencode, ToBencode};
+
+pub const BLOCK_SIZE: u64 = 16384;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Info {
+    name: Vec<u8>,
+    name_utf8: Option<Vec<u8>>,
+    piece_length: u64,
+    pieces: Vec<[u8; 20]>,
+    files: Vec<FileEntry>,
+    multi_file: bool,
+    private: bool,
+    source: Option<Vec<u8>>,
+    meta_version: Option<u64>,
+    raw: BencodeObject
+}
+
+impl Info {
+
+    pub fn get_name(&self) -> String {
+        String::from_utf8_lossy(&self.name).into_owned()
+    }
+
+    pub fn get_name_bytes(&self) -> &[u8] {
+        &self.name
+    }
+
+    pub fn get_name_utf8(&self) -> Option<&[u8]> {
+        self.name_utf8.as_deref()
+    }
+
+    pub fn get_piece_length(&self) -> u64 {
+        self.piece_length
+    }
+
+    pub fn get_pieces(&self) -> &Vec<[u8; 20]> {
+        &self.pieces
+    }
+
+    pub fn get_piece_count(&self) -> usize {
+        let total = self.get_total_length();
+        if total == 0 {
+            return 0;
+        }
+
+        total.div_ceil(self.piece_length) as usize
+    }
+
+    pub fn get_files(&self) -> &Vec<FileEntry> {
+        &self.files
+    }
+
+    pub fn get_total_length(&self) -> u64 {
+        self.files.iter().map(|f| f.get_length()).sum()
+    }
+
+    pub fn is_multi_file(&self) -> bool {
+        self.multi_file
+    }
+
+    pub fn is_private(&self) -> bool {
+        self.private
+    }
+
+    pub fn get_source(&self) -> Option<String> {
+        self.source.as_ref().map(|s| String::from_utf8_lossy(s).into_owned())
+    }
+
+    pub fn get_meta_version(&self) -> Option<u64> {
+        self.meta_version
+    }
+
+    pub fn has_v1(&self) -> bool {
+        self.raw.contains_key("pieces")
+    }
+
+    pub fn has_v2(&self) -> bool {
+        self.meta_version == Some(2) && self.raw.contains_key("file tree")
+    }
+
+    pub fn get_info_hash_v1(&self) -> Option<InfoHash> {
+        if !self.has_v1() {
+            return None;
+        }
+
+        Some(InfoHash::v1_from_info(&self.raw.to_bencode()))
+    }
+
+    pub fn get_info_hash_v2(&self) -> Option<InfoHash> {
+        if !self.has_v2() {
+            return None;
+        }
+
+        Some(InfoHash::v2_from_info(&self.raw.to_bencode()))
+    }
+
+    pub fn get_info_hash(&self) -> InfoHash {
+        self.get_info_hash_v1()
+            .or_else(|| self.get_info_hash_v2())
+            .unwrap_or_else(|| InfoHash::v1_from_info(&self.raw.to_bencode()))
+    }
+
+    pub fn get_raw(&self) -> &BencodeObject {
+        &self.raw
+    }
+}
+
+fn parse_file_tree(tree: &BencodeObject, prefix: &mut Vec<Vec<u8>>, files: &mut Vec<FileEntry>) -> io::Result<()> {
+    for (key, value) in tree.iter() {
+        let node = value.as_any().downcast_ref::<BencodeObject>()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "File tree node must be a dictionary"))?;
+
+        if key.as_bytes().is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "File tree has a file without a name"));
+        }
+
+        prefix.push(key.as_bytes().to_vec());
+
+        match node.get::<BencodeObject>("") {
+            Some(file) => {
+                let length = file.get::<BencodeNumber>("length")
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "File tree entry is missing length"))?
+                    .parse::<u64>()?;
+
+                let mut entry = FileEntry::new(prefix.clone(), length);
+                entry.read_attributes(file)?;
+                if let Some(root) = file.get::<BencodeBytes>("pieces root") {
+                    entry.set_pieces_root(root.as_bytes().try_into()
+                        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Pieces root must be 32 bytes"))?);
+                }
+                files.push(entry);
+            }
+            None => parse_file_tree(node, prefix, files)?
+        }
+
+        prefix.pop();
+    }
+
+    Ok(())
+}
+
+impl TryFrom<&BencodeObject> for Info {
+
+    type Error = io::Error;
+
+    fn try_from(ben: &BencodeObject) -> io::Result<Self> {
+        let name = ben.get::<BencodeBytes>("name")
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Info is missing name"))?
+            .as_bytes().to_vec();
+
+        let name_utf8 = ben.get::<BencodeBytes>("name.utf-8").map(|n| n.as_bytes().to_vec());
+
+        let piece_length = ben.get::<BencodeNumber>("piece length")
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Info is missing piece length"))?
+            .parse::<u64>()?;
+
+        if piece_length == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Piece length must be greater than zero"));
+        }
+
+        let meta_version = match ben.get::<BencodeNumber>("meta version") {
+            Some(v) => Some(v.parse::<u64>()?),
+            None => None
+        };
+
+        let mut pieces = Vec::new();
+        if let Some(p) = ben.get::<BencodeBytes>("pieces") {
+            if !p.as_bytes().len().is_multiple_of(20) {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "Pieces length must be a multiple of 20"));
+            }
+
+            pieces = p.as_bytes().chunks(20).map(|c| c.try_into().unwrap()).collect();
+        }
+
+        let mut files = Vec::new();
+        let mut multi_file = false;
+
+        if let Some(list) = ben.get::<BencodeArray>("files") {
+            multi_file = true;
+            for file in list.iter() {
+                let file = file.as_any().downcast_ref::<BencodeObject>()
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "File entry must be a dictionary"))?;
+                files.push(FileEntry::try_from(file)?);
+            }
+
+        } else if let Some(length) = ben.get::<BencodeNumber>("length") {
+            files.push(FileEntry::new(vec![name.clone()], length.parse::<u64>()?));
+        }
+
+        if meta_version == Some(2) {
+            if let Some(tree) = ben.get::<BencodeObject>("file tree") {
+                let mut tree_files = Vec::new();
+                parse_file_tree(tree, &mut Vec::new(), &mut tree_files)?;
+
+                if files.is_empty() {
+                    multi_file = !(tree_files.len() == 1 && tree_files[0].get_path().len() == 1 && tree.len() == 1);
+                    files = tree_files;
+
+                } else {
+                    for tree_file in tree_files {
+                        if let (Some(file), Some(root)) = (files.iter_mut().find(|f| f.get_path() == tree_file.get_path()), tree_file.get_pieces_root()) {
+                            file.set_pieces_root(*root);
+                        }
+                    }
+                }
+            }
+        }
+
+        if files.is_empty() && !ben.contains_key("file tree") {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Info has neither length, files nor file tree"));
+        }
+
+        if ben.contains_key("pieces") {
+            let total: u64 = files.iter().map(|f| f.get_length()).sum();
+            if pieces.len() as u64 != total.div_ceil(piece_length) {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "Piece count does not match total length"));
+            }
+        }
+
+        let private = match ben.get::<BencodeNumber>("private") {
+            Some(p) => p.parse::<i64>()? == 1,
+            None => false
+        };
+
+        let source = ben.get::<BencodeBytes>("source").map(|s| s.as_bytes().to_vec());
+
+        Ok(Self {
+            name,
+            name_utf8,
+            piece_length,
+            pieces,
+            files,
+            multi_file,
+            private,
+            source,
+            meta_version,
+            raw: ben.clone()
+        })
+    }
+}
+
+impl FromBencode for Info {
+
+    fn from_bencode_with_offset(buf: &[u8]) -> io::Result<(Self, usize)> {
+        let (ben, off) = BencodeObject::from_bencode_with_offset(buf)?;
+        Ok((Self::try_from(&ben)?, off))
+    }
+}
+
+impl ToBencode for Info {
+
+    fn to_bencode(&self) -> Vec<u8> {
+        self.raw.to_bencode()
+    }
+}
@@ -0,0 +1,59 @@
+use std::{fmt, io};
+use std::fmt::Formatter;
+use crate::utils::hex;
+use crate::utils::sha1::Sha1;
+use crate::utils::sha256::Sha256;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum InfoHash {
+    V1([u8; 20]),
+    V2([u8; 32])
+}
+
+impl InfoHash {
+
+    pub fn v1_from_info(info: &[u8]) -> Self {
+        Self::V1(Sha1::digest(info))
+    }
+
+    pub fn v2_from_info(info: &[u8]) -> Self {
+        Self::V2(Sha256::digest(info))
+    }
+
+    pub fn from_bytes(buf: &[u8]) -> io::Result<Self> {
+        match buf.len() {
+            20 => Ok(Self::V1(buf.try_into().unwrap())),
+            32 => Ok(Self::V2(buf.try_into().unwrap())),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidInput, "Info hash must be 20 or 32 bytes"))
+        }
+    }
+
+    pub fn from_hex(s: &str) -> io::Result<Self> {
+        Self::from_bytes(&hex::decode(s)?)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Self::V1(hash) => hash,
+            Self::V2(hash) => hash
+        }
+    }
+
+    pub fn truncated(&self) -> [u8; 20] {
+        match self {
+            Self::V1(hash) => *hash,
+            Self::V2(hash) => hash[..20].try_into().unwrap()
+        }
+    }
+
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.as_bytes())
+    }
+}
+
+impl fmt::Display for InfoHash {
+
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_hex())
+    }
+}
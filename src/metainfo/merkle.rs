@@ -0,0 +1,39 @@
+use crate::utils::sha256::Sha256;
+
+pub fn hash_pair(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut sha = Sha256::new();
+    sha.update(left);
+    sha.update(right);
+    sha.finalize()
+}
+
+pub fn pad_hash(depth: u32) -> [u8; 32] {
+    let mut hash = [0u8; 32];
+    for _ in 0..depth {
+        hash = hash_pair(&hash, &hash);
+    }
+    hash
+}
+
+pub fn root(leaves: &[[u8; 32]], width: usize, pad: [u8; 32]) -> [u8; 32] {
+    let width = width.max(leaves.len()).next_power_of_two();
+
+    let mut layer = leaves.to_vec();
+    let mut pad = pad;
+    let mut size = width;
+
+    while size > 1 {
+        if layer.len() % 2 == 1 {
+            layer.push(pad);
+        }
+
+        layer = layer.chunks(2).map(|pair| hash_pair(&pair[0], &pair[1])).collect();
+        pad = hash_pair(&pad, &pad);
+        size /= 2;
+    }
+
+    match layer.first() {
+        Some(hash) => *hash,
+        None => pad
+    }
+}
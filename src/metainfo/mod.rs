@@ -0,0 +1,12 @@
+pub mod info_hash;
+pub mod file_attributes;
+pub mod file_entry;
+pub mod info;
+pub mod merkle;
+pub mod torrent;
+pub mod torrent_builder;
+pub mod verify;
+pub mod piece_layout;
+pub mod safe_path;
+pub mod announce_list;
+pub mod web_seeds;
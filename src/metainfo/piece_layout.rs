@@ -0,0 +1,176 @@
+use std::ops::Range;
+use crate::metainfo::info::Info;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct FileSlice {
+    file: usize,
+    offset: u64,
+    length: u64
+}
+
+impl FileSlice {
+
+    pub fn get_file(&self) -> usize {
+        self.file
+    }
+
+    pub fn get_offset(&self) -> u64 {
+        self.offset
+    }
+
+    pub fn get_length(&self) -> u64 {
+        self.length
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct LayoutFile {
+    offset: u64,
+    length: u64,
+    padding: bool
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PieceLayout {
+    piece_length: u64,
+    files: Vec<LayoutFile>,
+    piece_count: usize,
+    end: u64,
+    aligned: bool
+}
+
+impl PieceLayout {
+
+    pub fn new(piece_length: u64, files: &[(u64, bool)], aligned: bool) -> Self {
+        let mut layout = Vec::with_capacity(files.len());
+        let mut offset: u64 = 0;
+
+        for (length, padding) in files {
+            if aligned {
+                offset = offset.div_ceil(piece_length) * piece_length;
+            }
+
+            layout.push(LayoutFile {
+                offset,
+                length: *length,
+                padding: *padding
+            });
+            offset += length;
+        }
+
+        Self {
+            piece_length,
+            files: layout,
+            piece_count: offset.div_ceil(piece_length) as usize,
+            end: offset,
+            aligned
+        }
+    }
+
+    pub fn get_piece_length(&self) -> u64 {
+        self.piece_length
+    }
+
+    pub fn get_piece_count(&self) -> usize {
+        self.piece_count
+    }
+
+    pub fn get_file_count(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_aligned(&self) -> bool {
+        self.aligned
+    }
+
+    pub fn get_file_offset(&self, file: usize) -> Option<u64> {
+        self.files.get(file).map(|f| f.offset)
+    }
+
+    pub fn is_padding(&self, file: usize) -> bool {
+        self.files.get(file).is_some_and(|f| f.padding)
+    }
+
+    pub fn get_piece_size(&self, piece: usize) -> u64 {
+        self.get_piece_slices(piece).iter().map(|s| s.length).sum()
+    }
+
+    pub fn get_piece_slices(&self, piece: usize) -> Vec<FileSlice> {
+        if piece >= self.piece_count {
+            return Vec::new();
+        }
+
+        let start = piece as u64 * self.piece_length;
+        let end = (start + self.piece_length).min(self.end);
+
+        let mut slices = Vec::new();
+        let mut i = self.files.partition_point(|f| f.offset + f.length <= start);
+
+        while i < self.files.len() && self.files[i].offset < end {
+            let file = &self.files[i];
+            let from = start.max(file.offset);
+            let to = end.min(file.offset + file.length);
+
+            if to > from {
+                slices.push(FileSlice {
+                    file: i,
+                    offset: from - file.offset,
+                    length: to - from
+                });
+            }
+            i += 1;
+        }
+
+        slices
+    }
+
+    pub fn get_data_slices(&self, piece: usize) -> Vec<FileSlice> {
+        self.get_piece_slices(piece).into_iter().filter(|s| !self.files[s.file].padding).collect()
+    }
+
+    pub fn get_file_pieces(&self, file: usize) -> Range<usize> {
+        match self.files.get(file) {
+            Some(f) if f.length > 0 => {
+                let first = (f.offset / self.piece_length) as usize;
+                let last = ((f.offset + f.length - 1) / self.piece_length) as usize;
+                first..last + 1
+            }
+            Some(f) => {
+                let piece = (f.offset / self.piece_length) as usize;
+                piece..piece
+            }
+            None => 0..0
+        }
+    }
+
+    pub fn locate(&self, offset: u64) -> Option<(usize, u64)> {
+        if offset >= self.end {
+            return None;
+        }
+
+        let i = self.files.partition_point(|f| f.offset + f.length <= offset);
+        let file = self.files.get(i)?;
+
+        if offset < file.offset {
+            return None;
+        }
+
+        Some((i, offset - file.offset))
+    }
+
+    pub fn locate_piece(&self, piece: usize, offset: u64) -> Option<(usize, u64)> {
+        if piece >= self.piece_count || offset >= self.piece_length {
+            return None;
+        }
+
+        self.locate(piece as u64 * self.piece_length + offset)
+    }
+}
+
+impl From<&Info> for PieceLayout {
+
+    fn from(info: &Info) -> Self {
+        let files: Vec<(u64, bool)> = info.get_files().iter().map(|f| (f.get_length(), f.is_padding())).collect();
+        Self::new(info.get_piece_length(), &files, !info.has_v1())
+    }
+}
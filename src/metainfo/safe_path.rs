@@ -0,0 +1,205 @@
+use std::io;
+use std::path::PathBuf;
+use crate::metainfo::file_entry::FileEntry;
+use crate::metainfo::torrent::Torrent;
+
+const RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL",
+    "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9",
+    "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9"
+];
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PathPolicy {
+    Reject,
+    Rewrite
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TextEncoding {
+    Utf8,
+    Latin1
+}
+
+impl TextEncoding {
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().replace('_', "-").as_str() {
+            "utf-8" | "utf8" => Some(Self::Utf8),
+            "iso-8859-1" | "iso8859-1" | "latin1" | "latin-1" | "windows-1252" | "cp1252" => Some(Self::Latin1),
+            _ => None
+        }
+    }
+
+    pub fn decode(&self, buf: &[u8]) -> Option<String> {
+        match self {
+            Self::Utf8 => String::from_utf8(buf.to_vec()).ok(),
+            Self::Latin1 => Some(buf.iter().map(|&b| b as char).collect())
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PathIssue {
+    EmptyPath,
+    EmptyComponent,
+    CurrentDir,
+    ParentDir,
+    Separator,
+    Nul,
+    ControlCharacter,
+    InvalidCharacter,
+    ReservedName,
+    TrailingDotOrSpace,
+    InvalidEncoding
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SafePath {
+    path: PathBuf,
+    issues: Vec<PathIssue>
+}
+
+impl SafePath {
+
+    pub fn get_path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    pub fn get_issues(&self) -> &Vec<PathIssue> {
+        &self.issues
+    }
+
+    pub fn was_rewritten(&self) -> bool {
+        !self.issues.is_empty()
+    }
+}
+
+pub fn sanitize_component(raw: &[u8], encoding: TextEncoding, policy: PathPolicy, issues: &mut Vec<PathIssue>) -> io::Result<String> {
+    let start = issues.len();
+
+    let decoded = match encoding.decode(raw) {
+        Some(s) => s,
+        None => {
+            issues.push(PathIssue::InvalidEncoding);
+            String::from_utf8_lossy(raw).into_owned()
+        }
+    };
+
+    let mut component = String::with_capacity(decoded.len());
+    for c in decoded.chars() {
+        match c {
+            '/' | '\\' => {
+                issues.push(PathIssue::Separator);
+                component.push('_');
+            }
+            '\0' => {
+                issues.push(PathIssue::Nul);
+                component.push('_');
+            }
+            '<' | '>' | ':' | '"' | '|' | '?' | '*' => {
+                issues.push(PathIssue::InvalidCharacter);
+                component.push('_');
+            }
+            c if c.is_control() => {
+                issues.push(PathIssue::ControlCharacter);
+                component.push('_');
+            }
+            c => component.push(c)
+        }
+    }
+
+    match component.as_str() {
+        "" => {
+            issues.push(PathIssue::EmptyComponent);
+            component = "_".to_string();
+        }
+        "." => {
+            issues.push(PathIssue::CurrentDir);
+            component = "_".to_string();
+        }
+        ".." => {
+            issues.push(PathIssue::ParentDir);
+            component = "__".to_string();
+        }
+        _ => {}
+    }
+
+    if component.ends_with('.') || component.ends_with(' ') {
+        issues.push(PathIssue::TrailingDotOrSpace);
+        let trimmed = component.trim_end_matches(['.', ' ']).len();
+        component.truncate(trimmed);
+        component.push('_');
+    }
+
+    let stem = component.split('.').next().unwrap_or("").trim_end().to_ascii_uppercase();
+    if RESERVED_NAMES.contains(&stem.as_str()) {
+        issues.push(PathIssue::ReservedName);
+        component.insert(0, '_');
+    }
+
+    if policy == PathPolicy::Reject && issues.len() > start {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Unsafe path component: {:?}", issues[start])));
+    }
+
+    Ok(component)
+}
+
+pub fn sanitize_path(components: &[Vec<u8>], encoding: TextEncoding, policy: PathPolicy) -> io::Result<SafePath> {
+    let mut issues = Vec::new();
+
+    if components.is_empty() {
+        if policy == PathPolicy::Reject {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Empty file path"));
+        }
+
+        return Ok(SafePath {
+            path: PathBuf::from("_"),
+            issues: vec![PathIssue::EmptyPath]
+        });
+    }
+
+    let mut path = PathBuf::new();
+    for component in components {
+        path.push(sanitize_component(component, encoding, policy, &mut issues)?);
+    }
+
+    Ok(SafePath {
+        path,
+        issues
+    })
+}
+
+pub fn torrent_encoding(torrent: &Torrent) -> TextEncoding {
+    torrent.get_encoding()
+        .and_then(|e| TextEncoding::from_name(e))
+        .unwrap_or(TextEncoding::Utf8)
+}
+
+pub fn root_path(torrent: &Torrent, policy: PathPolicy) -> io::Result<SafePath> {
+    let info = torrent.get_info();
+    match info.get_name_utf8() {
+        Some(name) => sanitize_path(&[name.to_vec()], TextEncoding::Utf8, policy),
+        None => sanitize_path(&[info.get_name_bytes().to_vec()], torrent_encoding(torrent), policy)
+    }
+}
+
+pub fn file_path(torrent: &Torrent, file: &FileEntry, policy: PathPolicy) -> io::Result<SafePath> {
+    if !torrent.get_info().is_multi_file() {
+        return root_path(torrent, policy);
+    }
+
+    let relative = match file.get_path_utf8() {
+        Some(path) => sanitize_path(path, TextEncoding::Utf8, policy)?,
+        None => sanitize_path(file.get_path(), torrent_encoding(torrent), policy)?
+    };
+
+    let mut root = root_path(torrent, policy)?;
+    root.path.push(relative.path);
+    root.issues.extend(relative.issues);
+    Ok(root)
+}
+
+pub fn file_paths(torrent: &Torrent, policy: PathPolicy) -> io::Result<Vec<SafePath>> {
+    torrent.get_info().get_files().iter().map(|f| file_path(torrent, f, policy)).collect()
+}
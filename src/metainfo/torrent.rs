@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+use std::io;
+use crate::metainfo::announce_list::AnnounceList;
+use crate::metainfo::info::Info;
+use crate::metainfo::info_hash::InfoHash;
+use crate::metainfo::web_seeds::WebSeeds;
+use crate::variables::bencode_array::BencodeArray;
+use crate::variables::bencode_bytes::BencodeBytes;
+use crate::variables::bencode_number::BencodeNumber;
+use crate::variables::bencode_object::{BencodeObject, GetObject, PutObject};
+use crate::variables::inter::bencode_variable::{BencodeVariable, FromBencode, ToBencode};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Torrent {
+    announce: Option<String>,
+    announce_list: AnnounceList,
+    url_list: WebSeeds,
+    http_seeds: WebSeeds,
+    comment: Option<String>,
+    created_by: Option<String>,
+    creation_date: Option<i64>,
+    encoding: Option<String>,
+    info: Info,
+    piece_layers: HashMap<[u8; 32], Vec<[u8; 32]>>
+}
+
+impl Torrent {
+
+    pub fn get_announce(&self) -> Option<&String> {
+        self.announce.as_ref()
+    }
+
+    pub fn get_announce_list(&self) -> &AnnounceList {
+        &self.announce_list
+    }
+
+    pub fn get_trackers(&self) -> AnnounceList {
+        if !self.announce_list.is_empty() {
+            return self.announce_list.clone();
+        }
+
+        let mut list = AnnounceList::new();
+        if let Some(announce) = &self.announce {
+            list.add_tier(vec![announce.clone()]);
+        }
+        list
+    }
+
+    pub fn get_url_list(&self) -> &WebSeeds {
+        &self.url_list
+    }
+
+    pub fn get_http_seeds(&self) -> &WebSeeds {
+        &self.http_seeds
+    }
+
+    pub fn get_comment(&self) -> Option<&String> {
+        self.comment.as_ref()
+    }
+
+    pub fn get_created_by(&self) -> Option<&String> {
+        self.created_by.as_ref()
+    }
+
+    pub fn get_creation_date(&self) -> Option<i64> {
+        self.creation_date
+    }
+
+    pub fn get_encoding(&self) -> Option<&String> {
+        self.encoding.as_ref()
+    }
+
+    pub fn get_info(&self) -> &Info {
+        &self.info
+    }
+
+    pub fn get_info_hash(&self) -> InfoHash {
+        self.info.get_info_hash()
+    }
+
+    pub fn get_piece_layers(&self) -> &HashMap<[u8; 32], Vec<[u8; 32]>> {
+        &self.piece_layers
+    }
+
+    pub fn get_piece_layer(&self, pieces_root: &[u8; 32]) -> Option<&Vec<[u8; 32]>> {
+        self.piece_layers.get(pieces_root)
+    }
+}
+
+fn get_string(ben: &BencodeObject, key: &str) -> Option<String> {
+    ben.get::<BencodeBytes>(key).map(|b| String::from_utf8_lossy(b.as_bytes()).into_owned())
+}
+
+impl TryFrom<&BencodeObject> for Torrent {
+
+    type Error = io::Error;
+
+    fn try_from(ben: &BencodeObject) -> io::Result<Self> {
+        let info = Info::try_from(ben.get::<BencodeObject>("info")
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Torrent is missing info"))?)?;
+
+        let announce_list = match ben.get::<BencodeArray>("announce-list") {
+            Some(list) => AnnounceList::try_from(list)?,
+            None => AnnounceList::new()
+        };
+
+        let creation_date = match ben.get::<BencodeNumber>("creation date") {
+            Some(d) => Some(d.parse::<i64>()?),
+            None => None
+        };
+
+        let mut piece_layers = HashMap::new();
+        if let Some(layers) = ben.get::<BencodeObject>("piece layers") {
+            for (root, hashes) in layers.iter() {
+                let root: [u8; 32] = root.as_bytes().try_into()
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Piece layer key must be 32 bytes"))?;
+
+                let hashes = hashes.as_any().downcast_ref::<BencodeBytes>()
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Piece layer must be bytes"))?
+                    .as_bytes();
+
+                if !hashes.len().is_multiple_of(32) {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, "Piece layer length must be a multiple of 32"));
+                }
+
+                piece_layers.insert(root, hashes.chunks(32).map(|c| c.try_into().unwrap()).collect());
+            }
+        }
+
+        Ok(Self {
+            announce: get_string(ben, "announce"),
+            announce_list,
+            url_list: WebSeeds::from_object(ben, "url-list"),
+            http_seeds: WebSeeds::from_object(ben, "httpseeds"),
+            comment: get_string(ben, "comment"),
+            created_by: get_string(ben, "created by"),
+            creation_date,
+            encoding: get_string(ben, "encoding"),
+            info,
+            piece_layers
+        })
+    }
+}
+
+impl FromBencode for Torrent {
+
+    fn from_bencode_with_offset(buf: &[u8]) -> io::Result<(Self, usize)> {
+        let (ben, off) = BencodeObject::from_bencode_with_offset(buf)?;
+        Ok((Self::try_from(&ben)?, off))
+    }
+}
+
+impl ToBencode for Torrent {
+
+    fn to_bencode(&self) -> Vec<u8> {
+        let mut ben = BencodeObject::new();
+
+        if let Some(announce) = &self.announce {
+            ben.put("announce", announce.as_str());
+        }
+
+        if !self.announce_list.is_empty() {
+            ben.put("announce-list", BencodeArray::from(&self.announce_list));
+        }
+
+        if let Some(comment) = &self.comment {
+            ben.put("comment", comment.as_str());
+        }
+
+        if let Some(created_by) = &self.created_by {
+            ben.put("created by", created_by.as_str());
+        }
+
+        if let Some(creation_date) = self.creation_date {
+            ben.put("creation date", creation_date);
+        }
+
+        if let Some(encoding) = &self.encoding {
+            ben.put("encoding", encoding.as_str());
+        }
+
+        self.http_seeds.put_into(&mut ben, "httpseeds");
+        ben.put("info", self.info.get_raw().clone());
+
+        if !self.piece_layers.is_empty() {
+            let mut roots: Vec<&[u8; 32]> = self.piece_layers.keys().collect();
+            roots.sort();
+
+            let mut layers = BencodeObject::new();
+            for root in roots {
+                layers.put(BencodeBytes::from(*root), self.piece_layers[root].concat());
+            }
+            ben.put("piece layers", layers);
+        }
+
+        self.url_list.put_into(&mut ben, "url-list");
+
+        ben.to_bencode()
+    }
+}
@@ -0,0 +1,593 @@
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use crate::metainfo::announce_list::AnnounceList;
+use crate::metainfo::file_attributes::FileAttributes;
+use crate::metainfo::file_entry::FileEntry;
+use crate::metainfo::info::BLOCK_SIZE;
+use crate::metainfo::merkle;
+use crate::metainfo::torrent::Torrent;
+use crate::metainfo::web_seeds::WebSeeds;
+use crate::utils::parallel;
+use crate::utils::sha1::Sha1;
+use crate::utils::sha256::Sha256;
+use crate::variables::bencode_array::{AddArray, BencodeArray};
+use crate::variables::bencode_bytes::BencodeBytes;
+use crate::variables::bencode_object::{BencodeObject, GetObject, PutObject};
+
+pub const MIN_PIECE_LENGTH: u64 = 16384;
+pub const MAX_PIECE_LENGTH: u64 = 16 * 1024 * 1024;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TorrentVersion {
+    V1,
+    V2,
+    Hybrid
+}
+
+type PieceLayer = ([u8; 32], Vec<[u8; 32]>);
+
+#[derive(Debug, Clone)]
+struct SourceFile {
+    disk_path: Option<PathBuf>,
+    entry: FileEntry
+}
+
+#[derive(Debug, Default)]
+struct OpenFile {
+    index: Option<usize>,
+    file: Option<File>
+}
+
+impl OpenFile {
+
+    fn read_at(&mut self, index: usize, path: &Path, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+        if self.file.is_none() || self.index != Some(index) {
+            self.file = Some(File::open(path)?);
+            self.index = Some(index);
+        }
+
+        let file = self.file.as_mut().unwrap();
+        file.seek(SeekFrom::Start(offset))?;
+        file.read_exact(buf)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TorrentBuilder {
+    path: PathBuf,
+    name: Option<String>,
+    piece_length: Option<u64>,
+    version: TorrentVersion,
+    trackers: AnnounceList,
+    web_seeds: Vec<String>,
+    http_seeds: Vec<String>,
+    comment: Option<String>,
+    created_by: Option<String>,
+    creation_date: Option<i64>,
+    private: bool,
+    source: Option<String>,
+    pad_files: bool,
+    detect_attributes: bool,
+    threads: usize
+}
+
+impl TorrentBuilder {
+
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            name: None,
+            piece_length: None,
+            version: TorrentVersion::V1,
+            trackers: AnnounceList::new(),
+            web_seeds: Vec::new(),
+            http_seeds: Vec::new(),
+            comment: None,
+            created_by: None,
+            creation_date: None,
+            private: false,
+            source: None,
+            pad_files: false,
+            detect_attributes: false,
+            threads: 1
+        }
+    }
+
+    pub fn set_name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    pub fn set_piece_length(mut self, piece_length: u64) -> Self {
+        self.piece_length = Some(piece_length);
+        self
+    }
+
+    pub fn set_version(mut self, version: TorrentVersion) -> Self {
+        self.version = version;
+        self
+    }
+
+    pub fn add_tracker(mut self, url: &str) -> Self {
+        self.trackers.add_tier(vec![url.to_string()]);
+        self
+    }
+
+    pub fn add_tracker_tier(mut self, urls: &[&str]) -> Self {
+        self.trackers.add_tier(urls.iter().map(|u| u.to_string()).collect());
+        self
+    }
+
+    pub fn set_trackers(mut self, trackers: AnnounceList) -> Self {
+        self.trackers = trackers;
+        self
+    }
+
+    pub fn add_web_seed(mut self, url: &str) -> Self {
+        self.web_seeds.push(url.to_string());
+        self
+    }
+
+    pub fn add_http_seed(mut self, url: &str) -> Self {
+        self.http_seeds.push(url.to_string());
+        self
+    }
+
+    pub fn set_comment(mut self, comment: &str) -> Self {
+        self.comment = Some(comment.to_string());
+        self
+    }
+
+    pub fn set_created_by(mut self, created_by: &str) -> Self {
+        self.created_by = Some(created_by.to_string());
+        self
+    }
+
+    pub fn set_creation_date(mut self, creation_date: i64) -> Self {
+        self.creation_date = Some(creation_date);
+        self
+    }
+
+    pub fn set_private(mut self, private: bool) -> Self {
+        self.private = private;
+        self
+    }
+
+    pub fn set_source(mut self, source: &str) -> Self {
+        self.source = Some(source.to_string());
+        self
+    }
+
+    pub fn set_pad_files(mut self, pad_files: bool) -> Self {
+        self.pad_files = pad_files;
+        self
+    }
+
+    pub fn set_detect_attributes(mut self, detect_attributes: bool) -> Self {
+        self.detect_attributes = detect_attributes;
+        self
+    }
+
+    pub fn set_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    pub fn build(&self) -> io::Result<Torrent> {
+        self.build_with_progress(|_, _| {})
+    }
+
+    pub fn build_with_progress<F>(&self, progress: F) -> io::Result<Torrent>
+    where
+        F: Fn(u64, u64) + Sync
+    {
+        let name = match &self.name {
+            Some(name) => name.clone(),
+            None => self.path.file_name()
+                .and_then(|n| n.to_str())
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Path has no usable file name"))?
+                .to_string()
+        };
+
+        let mut url_list = WebSeeds::new();
+        for url in &self.web_seeds {
+            url_list.add(url)?;
+        }
+
+        let mut http_seeds = WebSeeds::new();
+        for url in &self.http_seeds {
+            http_seeds.add(url)?;
+        }
+
+        let metadata = fs::metadata(&self.path)?;
+        let multi_file = metadata.is_dir();
+
+        let files = if multi_file {
+            let mut files = Vec::new();
+            walk(&self.path, &mut Vec::new(), self.detect_attributes, &mut files)?;
+            files
+
+        } else {
+            let mut entry = FileEntry::new(vec![name.as_bytes().to_vec()], metadata.len());
+            if self.detect_attributes {
+                entry.set_attributes(detect_attributes(&name, &metadata));
+            }
+
+            vec![SourceFile {
+                disk_path: Some(self.path.clone()),
+                entry
+            }]
+        };
+
+        if files.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "No files to add to torrent"));
+        }
+
+        let total: u64 = files.iter().map(|f| f.entry.get_length()).sum();
+
+        let piece_length = match self.piece_length {
+            Some(piece_length) => {
+                if !piece_length.is_power_of_two() || piece_length < MIN_PIECE_LENGTH {
+                    return Err(io::Error::new(io::ErrorKind::InvalidInput, "Piece length must be a power of two of at least 16 KiB"));
+                }
+                piece_length
+            }
+            None => default_piece_length(total)
+        };
+
+        let mut info = BencodeObject::new();
+        info.put("name", name.as_str());
+        info.put("piece length", piece_length);
+
+        if self.private {
+            info.put("private", 1);
+        }
+
+        if let Some(source) = &self.source {
+            info.put("source", source.as_str());
+        }
+
+        let mut ben = BencodeObject::new();
+
+        let v1_files = match multi_file && (self.pad_files || self.version == TorrentVersion::Hybrid) {
+            true => {
+                let entries: Vec<FileEntry> = files.iter().map(|f| f.entry.clone()).collect();
+                let mut real = files.iter();
+                insert_padding(&entries, piece_length).into_iter().map(|entry| SourceFile {
+                    disk_path: match entry.is_padding() {
+                        true => None,
+                        false => real.next().and_then(|f| f.disk_path.clone())
+                    },
+                    entry
+                }).collect()
+            }
+            false => files.clone()
+        };
+
+        let v1_total: u64 = match self.version {
+            TorrentVersion::V2 => 0,
+            _ => v1_files.iter().map(|f| f.entry.get_length()).sum()
+        };
+        let grand_total = match self.version {
+            TorrentVersion::Hybrid => v1_total + total,
+            _ => v1_total.max(total)
+        };
+
+        if self.version != TorrentVersion::V2 {
+            let pieces = hash_v1(&v1_files, v1_total, piece_length, self.threads, &|done, _| progress(done, grand_total))?;
+            info.put("pieces", pieces);
+
+            if multi_file {
+                let mut list = BencodeArray::new();
+                for file in &v1_files {
+                    list.push(file.entry.to_v1_object());
+                }
+                info.put("files", list);
+
+            } else {
+                info.put("length", total);
+            }
+        }
+
+        if self.version != TorrentVersion::V1 {
+            let base = match self.version {
+                TorrentVersion::Hybrid => v1_total,
+                _ => 0
+            };
+            let (entries, layers) = hash_v2(&files, total, piece_length, self.threads, &|done, _| progress(base + done, grand_total))?;
+
+            let mut tree = BencodeObject::new();
+            for entry in &entries {
+                insert_file_tree(&mut tree, entry);
+            }
+            info.put("file tree", tree);
+            info.put("meta version", 2);
+
+            if !layers.is_empty() {
+                let mut piece_layers = BencodeObject::new();
+                for (root, hashes) in layers {
+                    piece_layers.put(BencodeBytes::from(root), hashes.concat());
+                }
+                ben.put("piece layers", piece_layers);
+            }
+        }
+
+        ben.put("info", info);
+
+        if let Some(first) = self.trackers.get_first() {
+            ben.put("announce", first);
+
+            if self.trackers.get_tracker_count() > 1 {
+                ben.put("announce-list", BencodeArray::from(&self.trackers));
+            }
+        }
+
+        url_list.put_into(&mut ben, "url-list");
+        http_seeds.put_into(&mut ben, "httpseeds");
+
+        if let Some(comment) = &self.comment {
+            ben.put("comment", comment.as_str());
+        }
+
+        if let Some(created_by) = &self.created_by {
+            ben.put("created by", created_by.as_str());
+        }
+
+        if let Some(creation_date) = self.creation_date {
+            ben.put("creation date", creation_date);
+        }
+
+        ben.sort_keys();
+        Torrent::try_from(&ben)
+    }
+}
+
+pub fn insert_padding(files: &[FileEntry], piece_length: u64) -> Vec<FileEntry> {
+    let mut padded = Vec::with_capacity(files.len() * 2);
+    let mut offset = 0;
+
+    for (i, file) in files.iter().enumerate() {
+        if file.is_padding() {
+            continue;
+        }
+
+        padded.push(file.clone());
+        offset += file.get_length();
+
+        let remainder = offset % piece_length;
+        if remainder != 0 && i + 1 < files.len() {
+            padded.push(FileEntry::padding(piece_length - remainder));
+            offset += piece_length - remainder;
+        }
+    }
+
+    padded
+}
+
+pub fn default_piece_length(total: u64) -> u64 {
+    let mut piece_length = MIN_PIECE_LENGTH;
+    while piece_length < MAX_PIECE_LENGTH && total / piece_length > 1500 {
+        piece_length *= 2;
+    }
+    piece_length
+}
+
+fn detect_attributes(name: &str, metadata: &fs::Metadata) -> FileAttributes {
+    let mut attributes = FileAttributes::new();
+    attributes.set_hidden(name.starts_with('.'));
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        attributes.set_executable(metadata.permissions().mode() & 0o111 != 0);
+    }
+
+    #[cfg(not(unix))]
+    let _ = metadata;
+
+    attributes
+}
+
+fn walk(dir: &Path, prefix: &mut Vec<Vec<u8>>, detect: bool, files: &mut Vec<SourceFile>) -> io::Result<()> {
+    let mut entries = fs::read_dir(dir)?.collect::<io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|e| e.file_name());
+
+    for entry in entries {
+        let name = entry.file_name().into_string()
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "File name is not valid UTF-8"))?;
+        let file_type = entry.file_type()?;
+
+        prefix.push(name.clone().into_bytes());
+
+        if file_type.is_symlink() {
+            if detect {
+                if let Some(target) = symlink_target(&entry.path(), prefix)? {
+                    let mut file = FileEntry::new(prefix.clone(), 0);
+                    let mut attributes = FileAttributes::new();
+                    attributes.set_hidden(name.starts_with('.'));
+                    file.set_attributes(attributes);
+                    file.set_symlink_path(target);
+
+                    files.push(SourceFile {
+                        disk_path: None,
+                        entry: file
+                    });
+                }
+            }
+
+        } else if file_type.is_dir() {
+            walk(&entry.path(), prefix, detect, files)?;
+
+        } else if file_type.is_file() {
+            let metadata = entry.metadata()?;
+            let mut file = FileEntry::new(prefix.clone(), metadata.len());
+            if detect {
+                file.set_attributes(detect_attributes(&name, &metadata));
+            }
+
+            files.push(SourceFile {
+                disk_path: Some(entry.path()),
+                entry: file
+            });
+        }
+
+        prefix.pop();
+    }
+
+    Ok(())
+}
+
+fn symlink_target(link: &Path, prefix: &[Vec<u8>]) -> io::Result<Option<Vec<Vec<u8>>>> {
+    let target = fs::read_link(link)?;
+    let mut path = prefix[..prefix.len() - 1].to_vec();
+
+    for component in target.components() {
+        match component {
+            Component::Normal(name) => match name.to_str() {
+                Some(name) => path.push(name.as_bytes().to_vec()),
+                None => return Ok(None)
+            },
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if path.pop().is_none() {
+                    return Ok(None);
+                }
+            }
+            _ => return Ok(None)
+        }
+    }
+
+    Ok((!path.is_empty()).then_some(path))
+}
+
+fn read_range(files: &[SourceFile], offsets: &[u64], start: u64, buf: &mut [u8], open: &mut OpenFile) -> io::Result<()> {
+    let mut i = offsets.partition_point(|&o| o <= start).saturating_sub(1);
+    let mut pos = start;
+    let mut filled = 0;
+
+    while filled < buf.len() {
+        while i < files.len() && pos >= offsets[i] + files[i].entry.get_length() {
+            i += 1;
+        }
+
+        if i >= files.len() {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Read past the end of the torrent data"));
+        }
+
+        let file_offset = pos - offsets[i];
+        let n = ((files[i].entry.get_length() - file_offset) as usize).min(buf.len() - filled);
+
+        match &files[i].disk_path {
+            Some(path) => open.read_at(i, path, file_offset, &mut buf[filled..filled + n])?,
+            None => buf[filled..filled + n].fill(0)
+        }
+
+        filled += n;
+        pos += n as u64;
+    }
+
+    Ok(())
+}
+
+fn hash_v1<F>(files: &[SourceFile], total: u64, piece_length: u64, threads: usize, progress: &F) -> io::Result<Vec<u8>>
+where
+    F: Fn(u64, u64) + Sync
+{
+    let mut offsets = Vec::with_capacity(files.len());
+    let mut offset = 0;
+    for file in files {
+        offsets.push(offset);
+        offset += file.entry.get_length();
+    }
+
+    let done = AtomicU64::new(0);
+    let count = total.div_ceil(piece_length) as usize;
+
+    let hashes = parallel::map_with(count, threads, OpenFile::default, |open, i| {
+        let start = i as u64 * piece_length;
+        let mut buf = vec![0u8; piece_length.min(total - start) as usize];
+        read_range(files, &offsets, start, &mut buf, open)?;
+
+        let hash = Sha1::digest(&buf);
+        progress(done.fetch_add(buf.len() as u64, Ordering::SeqCst) + buf.len() as u64, total);
+        Ok(hash)
+    })?;
+
+    Ok(hashes.concat())
+}
+
+fn hash_v2<F>(files: &[SourceFile], total: u64, piece_length: u64, threads: usize, progress: &F) -> io::Result<(Vec<FileEntry>, Vec<PieceLayer>)>
+where
+    F: Fn(u64, u64) + Sync
+{
+    let mut jobs = Vec::new();
+    for (i, file) in files.iter().enumerate() {
+        for piece in 0..file.entry.get_length().div_ceil(piece_length) {
+            jobs.push((i, piece));
+        }
+    }
+
+    let done = AtomicU64::new(0);
+
+    let leaves = parallel::map_with(jobs.len(), threads, OpenFile::default, |open, j| {
+        let (i, piece) = jobs[j];
+        let start = piece * piece_length;
+        let mut buf = vec![0u8; piece_length.min(files[i].entry.get_length() - start) as usize];
+
+        let path = files[i].disk_path.as_ref()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Padding files have no v2 hashes"))?;
+        open.read_at(i, path, start, &mut buf)?;
+
+        let hashes: Vec<[u8; 32]> = buf.chunks(BLOCK_SIZE as usize).map(Sha256::digest).collect();
+        progress(done.fetch_add(buf.len() as u64, Ordering::SeqCst) + buf.len() as u64, total);
+        Ok(hashes)
+    })?;
+
+    let blocks_per_piece = (piece_length / BLOCK_SIZE) as usize;
+    let piece_pad = merkle::pad_hash(blocks_per_piece.trailing_zeros());
+
+    let mut entries = Vec::new();
+    let mut layers = Vec::new();
+    let mut leaves = leaves.into_iter();
+
+    for file in files {
+        let mut entry = file.entry.clone();
+        let pieces = file.entry.get_length().div_ceil(piece_length) as usize;
+
+        if pieces == 1 {
+            let blocks = leaves.next().unwrap();
+            entry.set_pieces_root(merkle::root(&blocks, blocks.len(), [0; 32]));
+
+        } else if pieces > 1 {
+            let layer: Vec<[u8; 32]> = (0..pieces)
+                .map(|_| merkle::root(&leaves.next().unwrap(), blocks_per_piece, [0; 32]))
+                .collect();
+
+            let root = merkle::root(&layer, layer.len(), piece_pad);
+            entry.set_pieces_root(root);
+            layers.push((root, layer));
+        }
+
+        entries.push(entry);
+    }
+
+    Ok((entries, layers))
+}
+
+fn insert_file_tree(tree: &mut BencodeObject, entry: &FileEntry) {
+    let path = entry.get_path();
+    let mut node = tree;
+
+    for component in &path[..path.len() - 1] {
+        if node.get::<BencodeObject>(component).is_none() {
+            node.put(component, BencodeObject::new());
+        }
+        node = node.get_mut::<BencodeObject>(component).unwrap();
+    }
+
+    node.put(&path[path.len() - 1], entry.to_v2_object());
+}
@@ -0,0 +1,243 @@
+use std::fs::File;
+use std::io;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use crate::metainfo::info::BLOCK_SIZE;
+use crate::metainfo::merkle;
+use crate::metainfo::piece_layout::{FileSlice, PieceLayout};
+use crate::metainfo::safe_path;
+use crate::metainfo::safe_path::PathPolicy;
+use crate::metainfo::torrent::Torrent;
+use crate::utils::parallel;
+use crate::utils::sha1::Sha1;
+use crate::utils::sha256::Sha256;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PieceStatus {
+    Complete,
+    Missing,
+    Corrupt
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum FileStatus {
+    Complete,
+    Incomplete,
+    Missing
+}
+
+#[derive(Debug, Clone)]
+pub struct VerifyReport {
+    pieces: Vec<PieceStatus>,
+    files: Vec<FileStatus>
+}
+
+impl VerifyReport {
+
+    pub fn get_pieces(&self) -> &Vec<PieceStatus> {
+        &self.pieces
+    }
+
+    pub fn get_piece_status(&self, index: usize) -> Option<PieceStatus> {
+        self.pieces.get(index).copied()
+    }
+
+    pub fn get_files(&self) -> &Vec<FileStatus> {
+        &self.files
+    }
+
+    pub fn get_file_status(&self, index: usize) -> Option<FileStatus> {
+        self.files.get(index).copied()
+    }
+
+    pub fn get_complete_count(&self) -> usize {
+        self.pieces.iter().filter(|p| **p == PieceStatus::Complete).count()
+    }
+
+    pub fn get_missing_pieces(&self) -> Vec<usize> {
+        self.pieces_with(PieceStatus::Missing)
+    }
+
+    pub fn get_corrupt_pieces(&self) -> Vec<usize> {
+        self.pieces_with(PieceStatus::Corrupt)
+    }
+
+    pub fn get_affected_files(&self) -> Vec<usize> {
+        (0..self.files.len()).filter(|&i| self.files[i] != FileStatus::Complete).collect()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.pieces.iter().all(|p| *p == PieceStatus::Complete)
+    }
+
+    fn pieces_with(&self, status: PieceStatus) -> Vec<usize> {
+        (0..self.pieces.len()).filter(|&i| self.pieces[i] == status).collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Expected {
+    V1([u8; 20]),
+    V2Layer([u8; 32]),
+    V2Root([u8; 32])
+}
+
+pub struct TorrentVerifier<'a> {
+    torrent: &'a Torrent,
+    dir: PathBuf,
+    threads: usize
+}
+
+impl<'a> TorrentVerifier<'a> {
+
+    pub fn new<P: AsRef<Path>>(torrent: &'a Torrent, dir: P) -> Self {
+        Self {
+            torrent,
+            dir: dir.as_ref().to_path_buf(),
+            threads: 1
+        }
+    }
+
+    pub fn set_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    pub fn verify(&self) -> io::Result<VerifyReport> {
+        self.verify_with_progress(|_, _| {})
+    }
+
+    pub fn verify_with_progress<F>(&self, progress: F) -> io::Result<VerifyReport>
+    where
+        F: Fn(usize, usize) + Sync
+    {
+        let info = self.torrent.get_info();
+        let pieces = self.map_pieces()?;
+        let paths: Vec<PathBuf> = safe_path::file_paths(self.torrent, PathPolicy::Rewrite)?
+            .into_iter()
+            .map(|p| self.dir.join(p.get_path()))
+            .collect();
+
+        let padding: Vec<bool> = info.get_files().iter().map(|f| f.is_padding()).collect();
+        let piece_length = info.get_piece_length();
+
+        let done = AtomicU64::new(0);
+        let statuses = parallel::map(pieces.len(), self.threads, |i| {
+            let (spans, expected) = &pieces[i];
+            let status = check_piece(&padding, piece_length, &paths, spans, expected);
+            progress(done.fetch_add(1, Ordering::SeqCst) as usize + 1, pieces.len());
+            status
+        })?;
+
+        let mut files: Vec<FileStatus> = info.get_files().iter().enumerate().map(|(i, f)| {
+            if f.is_padding() || f.get_length() == 0 || paths[i].is_file() {
+                FileStatus::Complete
+            } else {
+                FileStatus::Missing
+            }
+        }).collect();
+
+        for (i, (spans, _)) in pieces.iter().enumerate() {
+            if statuses[i] == PieceStatus::Complete {
+                continue;
+            }
+
+            for span in spans {
+                if files[span.get_file()] == FileStatus::Complete && !info.get_files()[span.get_file()].is_padding() {
+                    files[span.get_file()] = FileStatus::Incomplete;
+                }
+            }
+        }
+
+        Ok(VerifyReport {
+            pieces: statuses,
+            files
+        })
+    }
+
+    fn map_pieces(&self) -> io::Result<Vec<(Vec<FileSlice>, Expected)>> {
+        let info = self.torrent.get_info();
+        let layout = PieceLayout::from(info);
+
+        if info.has_v1() {
+            return Ok(info.get_pieces().iter()
+                .enumerate()
+                .map(|(i, hash)| (layout.get_piece_slices(i), Expected::V1(*hash)))
+                .collect());
+        }
+
+        let mut pieces = Vec::with_capacity(layout.get_piece_count());
+
+        for (i, file) in info.get_files().iter().enumerate() {
+            let range = layout.get_file_pieces(i);
+            if range.is_empty() {
+                continue;
+            }
+
+            let root = file.get_pieces_root()
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "File is missing pieces root"))?;
+
+            if range.len() == 1 {
+                pieces.push((layout.get_piece_slices(range.start), Expected::V2Root(*root)));
+                continue;
+            }
+
+            let layer = self.torrent.get_piece_layer(root)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Torrent is missing a piece layer"))?;
+
+            if layer.len() != range.len() {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "Piece layer does not match file length"));
+            }
+
+            for (piece, hash) in range.zip(layer.iter()) {
+                pieces.push((layout.get_piece_slices(piece), Expected::V2Layer(*hash)));
+            }
+        }
+
+        Ok(pieces)
+    }
+}
+
+fn check_piece(padding: &[bool], piece_length: u64, paths: &[PathBuf], spans: &[FileSlice], expected: &Expected) -> io::Result<PieceStatus> {
+    let mut buf = Vec::new();
+
+    for span in spans {
+        let start = buf.len();
+        buf.resize(start + span.get_length() as usize, 0);
+
+        if padding[span.get_file()] {
+            continue;
+        }
+
+        let mut file = match File::open(&paths[span.get_file()]) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(PieceStatus::Missing),
+            Err(e) => return Err(e)
+        };
+
+        if file.metadata()?.len() < span.get_offset() + span.get_length() {
+            return Ok(PieceStatus::Missing);
+        }
+
+        file.seek(SeekFrom::Start(span.get_offset()))?;
+        file.read_exact(&mut buf[start..])?;
+    }
+
+    let valid = match expected {
+        Expected::V1(hash) => Sha1::digest(&buf) == *hash,
+        Expected::V2Layer(hash) | Expected::V2Root(hash) => {
+            let leaves: Vec<[u8; 32]> = buf.chunks(BLOCK_SIZE as usize).map(Sha256::digest).collect();
+            let width = match expected {
+                Expected::V2Layer(_) => (piece_length / BLOCK_SIZE) as usize,
+                _ => leaves.len()
+            };
+            merkle::root(&leaves, width, [0; 32]) == *hash
+        }
+    };
+
+    Ok(match valid {
+        true => PieceStatus::Complete,
+        false => PieceStatus::Corrupt
+    })
+}
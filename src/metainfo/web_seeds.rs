@@ -0,0 +1,149 @@
+use std::io;
+use crate::variables::bencode_array::{AddArray, BencodeArray};
+use crate::variables::bencode_bytes::BencodeBytes;
+use crate::variables::bencode_object::{BencodeObject, GetObject, PutObject};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SeedListForm {
+    Single,
+    List
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct WebSeeds {
+    urls: Vec<String>,
+    entries: Vec<Vec<u8>>,
+    form: SeedListForm
+}
+
+impl WebSeeds {
+
+    pub fn new() -> Self {
+        Self {
+            urls: Vec::new(),
+            entries: Vec::new(),
+            form: SeedListForm::List
+        }
+    }
+
+    pub fn get_urls(&self) -> &Vec<String> {
+        &self.urls
+    }
+
+    pub fn get_rejected(&self) -> Vec<&[u8]> {
+        self.entries.iter()
+            .filter(|entry| !self.urls.iter().any(|u| u.as_bytes() == entry.as_slice()))
+            .map(|entry| entry.as_slice())
+            .collect()
+    }
+
+    pub fn get_form(&self) -> SeedListForm {
+        self.form
+    }
+
+    pub fn set_form(&mut self, form: SeedListForm) {
+        self.form = form;
+    }
+
+    pub fn len(&self) -> usize {
+        self.urls.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.urls.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &String> {
+        self.urls.iter()
+    }
+
+    pub fn add(&mut self, url: &str) -> io::Result<()> {
+        validate_url(url)?;
+
+        if !self.urls.iter().any(|u| u == url) {
+            self.urls.push(url.to_string());
+            self.entries.push(url.as_bytes().to_vec());
+        }
+        Ok(())
+    }
+
+    pub fn remove(&mut self, url: &str) -> bool {
+        let before = self.urls.len();
+        self.urls.retain(|u| u != url);
+        self.entries.retain(|entry| entry.as_slice() != url.as_bytes());
+        before != self.urls.len()
+    }
+
+    fn read_entry(&mut self, entry: &[u8]) {
+        if let Ok(url) = std::str::from_utf8(entry) {
+            if validate_url(url).is_ok() && !self.urls.iter().any(|u| u == url) {
+                self.urls.push(url.to_string());
+            }
+        }
+        self.entries.push(entry.to_vec());
+    }
+
+    pub fn from_object(ben: &BencodeObject, key: &str) -> Self {
+        let mut seeds = Self::new();
+
+        if let Some(url) = ben.get::<BencodeBytes>(key) {
+            seeds.form = SeedListForm::Single;
+            seeds.read_entry(url.as_bytes());
+
+        } else if let Some(list) = ben.get::<BencodeArray>(key) {
+            for url in list.iter() {
+                if let Some(url) = url.as_any().downcast_ref::<BencodeBytes>() {
+                    seeds.read_entry(url.as_bytes());
+                }
+            }
+        }
+
+        seeds
+    }
+
+    pub fn put_into(&self, ben: &mut BencodeObject, key: &str) {
+        if self.entries.is_empty() {
+            return;
+        }
+
+        if self.form == SeedListForm::Single && self.entries.len() == 1 {
+            ben.put(key, self.entries[0].clone());
+            return;
+        }
+
+        let mut list = BencodeArray::new();
+        for entry in &self.entries {
+            list.push(entry.clone());
+        }
+        ben.put(key, list);
+    }
+}
+
+impl Default for WebSeeds {
+
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn validate_url(url: &str) -> io::Result<()> {
+    let (scheme, rest) = url.split_once("://")
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "URL is missing a scheme"))?;
+
+    if !matches!(scheme.to_ascii_lowercase().as_str(), "http" | "https" | "ftp") {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "Web seed URL must use http, https or ftp"));
+    }
+
+    let host = rest.split(['/', '?', '#']).next().unwrap_or("");
+    let host = host.rsplit_once('@').map(|(_, h)| h).unwrap_or(host);
+
+    if host.is_empty() || host.starts_with(':') {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "URL is missing a host"));
+    }
+
+    if url.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "URL contains whitespace or control characters"));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,243 @@
+use std::io;
+use crate::metainfo::info_hash::InfoHash;
+use crate::tracker::query_string::{join_url, parse, parse_number, split_url};
+use crate::utils::url_encoding;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum AnnounceEvent {
+    Started,
+    Stopped,
+    Completed,
+    Paused
+}
+
+impl AnnounceEvent {
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "started" => Some(Self::Started),
+            "stopped" => Some(Self::Stopped),
+            "completed" => Some(Self::Completed),
+            "paused" => Some(Self::Paused),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Started => "started",
+            Self::Stopped => "stopped",
+            Self::Completed => "completed",
+            Self::Paused => "paused"
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AnnounceRequest {
+    info_hash: InfoHash,
+    peer_id: [u8; 20],
+    port: u16,
+    uploaded: u64,
+    downloaded: u64,
+    left: u64,
+    compact: bool,
+    no_peer_id: bool,
+    event: Option<AnnounceEvent>,
+    ip: Option<String>,
+    numwant: Option<u32>,
+    key: Option<String>,
+    tracker_id: Option<Vec<u8>>
+}
+
+impl AnnounceRequest {
+
+    pub fn new(info_hash: InfoHash, peer_id: [u8; 20], port: u16) -> Self {
+        Self {
+            info_hash: InfoHash::V1(info_hash.truncated()),
+            peer_id,
+            port,
+            uploaded: 0,
+            downloaded: 0,
+            left: 0,
+            compact: true,
+            no_peer_id: false,
+            event: None,
+            ip: None,
+            numwant: None,
+            key: None,
+            tracker_id: None
+        }
+    }
+
+    pub fn get_info_hash(&self) -> &InfoHash {
+        &self.info_hash
+    }
+
+    pub fn get_peer_id(&self) -> &[u8; 20] {
+        &self.peer_id
+    }
+
+    pub fn get_port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn get_uploaded(&self) -> u64 {
+        self.uploaded
+    }
+
+    pub fn set_uploaded(&mut self, uploaded: u64) {
+        self.uploaded = uploaded;
+    }
+
+    pub fn get_downloaded(&self) -> u64 {
+        self.downloaded
+    }
+
+    pub fn set_downloaded(&mut self, downloaded: u64) {
+        self.downloaded = downloaded;
+    }
+
+    pub fn get_left(&self) -> u64 {
+        self.left
+    }
+
+    pub fn set_left(&mut self, left: u64) {
+        self.left = left;
+    }
+
+    pub fn is_compact(&self) -> bool {
+        self.compact
+    }
+
+    pub fn set_compact(&mut self, compact: bool) {
+        self.compact = compact;
+    }
+
+    pub fn is_no_peer_id(&self) -> bool {
+        self.no_peer_id
+    }
+
+    pub fn set_no_peer_id(&mut self, no_peer_id: bool) {
+        self.no_peer_id = no_peer_id;
+    }
+
+    pub fn get_event(&self) -> Option<AnnounceEvent> {
+        self.event
+    }
+
+    pub fn set_event(&mut self, event: AnnounceEvent) {
+        self.event = Some(event);
+    }
+
+    pub fn get_ip(&self) -> Option<&String> {
+        self.ip.as_ref()
+    }
+
+    pub fn set_ip(&mut self, ip: &str) {
+        self.ip = Some(ip.to_string());
+    }
+
+    pub fn get_numwant(&self) -> Option<u32> {
+        self.numwant
+    }
+
+    pub fn set_numwant(&mut self, numwant: u32) {
+        self.numwant = Some(numwant);
+    }
+
+    pub fn get_key(&self) -> Option<&String> {
+        self.key.as_ref()
+    }
+
+    pub fn set_key(&mut self, key: &str) {
+        self.key = Some(key.to_string());
+    }
+
+    pub fn get_tracker_id(&self) -> Option<&Vec<u8>> {
+        self.tracker_id.as_ref()
+    }
+
+    pub fn set_tracker_id(&mut self, tracker_id: &[u8]) {
+        self.tracker_id = Some(tracker_id.to_vec());
+    }
+
+    pub fn to_url(&self, announce: &str) -> String {
+        let mut params = vec![
+            ("info_hash", url_encoding::encode(self.info_hash.as_bytes())),
+            ("peer_id", url_encoding::encode(&self.peer_id)),
+            ("port", self.port.to_string()),
+            ("uploaded", self.uploaded.to_string()),
+            ("downloaded", self.downloaded.to_string()),
+            ("left", self.left.to_string()),
+            ("compact", (self.compact as u8).to_string())
+        ];
+
+        if self.no_peer_id {
+            params.push(("no_peer_id", "1".to_string()));
+        }
+
+        if let Some(event) = self.event {
+            params.push(("event", event.name().to_string()));
+        }
+
+        if let Some(ip) = &self.ip {
+            params.push(("ip", url_encoding::encode(ip.as_bytes())));
+        }
+
+        if let Some(numwant) = self.numwant {
+            params.push(("numwant", numwant.to_string()));
+        }
+
+        if let Some(key) = &self.key {
+            params.push(("key", url_encoding::encode(key.as_bytes())));
+        }
+
+        if let Some(tracker_id) = &self.tracker_id {
+            params.push(("trackerid", url_encoding::encode(tracker_id)));
+        }
+
+        join_url(announce, &params)
+    }
+
+    pub fn from_url(url: &str) -> io::Result<Self> {
+        let (_, query) = split_url(url);
+        Self::from_query(query)
+    }
+
+    pub fn from_query(query: &str) -> io::Result<Self> {
+        let mut info_hash = None;
+        let mut peer_id = None;
+        let mut port = None;
+        let mut request = Self::new(InfoHash::V1([0; 20]), [0; 20], 0);
+
+        for (key, value) in parse(query)? {
+            let string = || String::from_utf8(value.clone())
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e));
+
+            match key.as_str() {
+                "info_hash" => info_hash = Some(InfoHash::V1(value.as_slice().try_into()
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "info_hash must be 20 bytes"))?)),
+                "peer_id" => peer_id = Some(<[u8; 20]>::try_from(value.as_slice())
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "peer_id must be 20 bytes"))?),
+                "port" => port = Some(parse_number::<u16>(&key, &value)?),
+                "uploaded" => request.uploaded = parse_number(&key, &value)?,
+                "downloaded" => request.downloaded = parse_number(&key, &value)?,
+                "left" => request.left = parse_number(&key, &value)?,
+                "compact" => request.compact = parse_number::<u8>(&key, &value)? != 0,
+                "no_peer_id" => request.no_peer_id = parse_number::<u8>(&key, &value)? != 0,
+                "event" => request.event = AnnounceEvent::from_name(&string()?),
+                "ip" => request.ip = Some(string()?),
+                "numwant" => request.numwant = Some(parse_number(&key, &value)?),
+                "key" => request.key = Some(string()?),
+                "trackerid" => request.tracker_id = Some(value),
+                _ => {}
+            }
+        }
+
+        request.info_hash = info_hash.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "info_hash not found"))?;
+        request.peer_id = peer_id.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "peer_id not found"))?;
+        request.port = port.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "port not found"))?;
+        Ok(request)
+    }
+}
@@ -0,0 +1,265 @@
+use std::io;
+use crate::compact::address_family::AddressFamily;
+use crate::compact::compact_peer::{decode_peers, split_peers};
+use crate::tracker::tracker_peer::TrackerPeer;
+use crate::variables::bencode_array::{AddArray, BencodeArray};
+use crate::variables::bencode_bytes::BencodeBytes;
+use crate::variables::bencode_number::BencodeNumber;
+use crate::variables::bencode_object::{BencodeObject, GetObject, PutObject};
+use crate::variables::inter::bencode_variable::{BencodeVariable, FromBencode, ToBencode};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PeerListForm {
+    Compact,
+    Dictionary
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AnnounceResponse {
+    failure_reason: Option<String>,
+    warning_message: Option<String>,
+    interval: Option<u32>,
+    min_interval: Option<u32>,
+    tracker_id: Option<Vec<u8>>,
+    complete: Option<u32>,
+    incomplete: Option<u32>,
+    peers: Vec<TrackerPeer>,
+    peer_form: PeerListForm
+}
+
+impl AnnounceResponse {
+
+    pub fn new(interval: u32) -> Self {
+        Self {
+            failure_reason: None,
+            warning_message: None,
+            interval: Some(interval),
+            min_interval: None,
+            tracker_id: None,
+            complete: None,
+            incomplete: None,
+            peers: Vec::new(),
+            peer_form: PeerListForm::Compact
+        }
+    }
+
+    pub fn failure(reason: &str) -> Self {
+        Self {
+            failure_reason: Some(reason.to_string()),
+            interval: None,
+            ..Self::new(0)
+        }
+    }
+
+    pub fn is_failure(&self) -> bool {
+        self.failure_reason.is_some()
+    }
+
+    pub fn get_failure_reason(&self) -> Option<&String> {
+        self.failure_reason.as_ref()
+    }
+
+    pub fn get_warning_message(&self) -> Option<&String> {
+        self.warning_message.as_ref()
+    }
+
+    pub fn set_warning_message(&mut self, message: &str) {
+        self.warning_message = Some(message.to_string());
+    }
+
+    pub fn get_interval(&self) -> Option<u32> {
+        self.interval
+    }
+
+    pub fn set_interval(&mut self, interval: u32) {
+        self.interval = Some(interval);
+    }
+
+    pub fn get_min_interval(&self) -> Option<u32> {
+        self.min_interval
+    }
+
+    pub fn set_min_interval(&mut self, min_interval: u32) {
+        self.min_interval = Some(min_interval);
+    }
+
+    pub fn get_tracker_id(&self) -> Option<&Vec<u8>> {
+        self.tracker_id.as_ref()
+    }
+
+    pub fn set_tracker_id(&mut self, tracker_id: &[u8]) {
+        self.tracker_id = Some(tracker_id.to_vec());
+    }
+
+    pub fn get_complete(&self) -> Option<u32> {
+        self.complete
+    }
+
+    pub fn set_complete(&mut self, complete: u32) {
+        self.complete = Some(complete);
+    }
+
+    pub fn get_incomplete(&self) -> Option<u32> {
+        self.incomplete
+    }
+
+    pub fn set_incomplete(&mut self, incomplete: u32) {
+        self.incomplete = Some(incomplete);
+    }
+
+    pub fn get_peers(&self) -> &Vec<TrackerPeer> {
+        &self.peers
+    }
+
+    pub fn add_peer(&mut self, peer: TrackerPeer) {
+        self.peers.push(peer);
+    }
+
+    pub fn get_peer_form(&self) -> PeerListForm {
+        self.peer_form
+    }
+
+    pub fn set_peer_form(&mut self, form: PeerListForm) {
+        self.peer_form = form;
+    }
+}
+
+impl TryFrom<&BencodeObject> for AnnounceResponse {
+
+    type Error = io::Error;
+
+    fn try_from(ben: &BencodeObject) -> io::Result<Self> {
+        let string = |key: &str| ben.get::<BencodeBytes>(key).map(|s| String::from_utf8_lossy(s.as_bytes()).into_owned());
+
+        let number = |key: &str| -> io::Result<Option<u32>> {
+            match ben.get::<BencodeNumber>(key) {
+                Some(n) => Ok(Some(n.parse::<u32>()?)),
+                None => Ok(None)
+            }
+        };
+
+        let mut peers = Vec::new();
+        let mut peer_form = PeerListForm::Compact;
+
+        if let Some(compact) = ben.get::<BencodeBytes>("peers") {
+            peers.extend(decode_peers(compact.as_bytes(), AddressFamily::V4)?.into_iter().map(TrackerPeer::from));
+
+        } else if let Some(list) = ben.get::<BencodeArray>("peers") {
+            peer_form = PeerListForm::Dictionary;
+            read_peer_list(list, &mut peers)?;
+        }
+
+        if let Some(compact) = ben.get::<BencodeBytes>("peers6") {
+            peers.extend(decode_peers(compact.as_bytes(), AddressFamily::V6)?.into_iter().map(TrackerPeer::from));
+
+        } else if let Some(list) = ben.get::<BencodeArray>("peers6") {
+            peer_form = PeerListForm::Dictionary;
+            read_peer_list(list, &mut peers)?;
+        }
+
+        let failure_reason = string("failure reason");
+        let interval = number("interval")?;
+
+        if failure_reason.is_none() && interval.is_none() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Announce response has no interval"));
+        }
+
+        Ok(Self {
+            failure_reason,
+            warning_message: string("warning message"),
+            interval,
+            min_interval: number("min interval")?,
+            tracker_id: ben.get::<BencodeBytes>("tracker id").map(|t| t.as_bytes().to_vec()),
+            complete: number("complete")?,
+            incomplete: number("incomplete")?,
+            peers,
+            peer_form
+        })
+    }
+}
+
+impl From<&AnnounceResponse> for BencodeObject {
+
+    fn from(response: &AnnounceResponse) -> Self {
+        let mut ben = BencodeObject::new();
+
+        if let Some(reason) = &response.failure_reason {
+            ben.put("failure reason", reason.as_str());
+        }
+
+        if let Some(message) = &response.warning_message {
+            ben.put("warning message", message.as_str());
+        }
+
+        if let Some(interval) = response.interval {
+            ben.put("interval", interval);
+        }
+
+        if let Some(min_interval) = response.min_interval {
+            ben.put("min interval", min_interval);
+        }
+
+        if let Some(tracker_id) = &response.tracker_id {
+            ben.put("tracker id", tracker_id.as_slice());
+        }
+
+        if let Some(complete) = response.complete {
+            ben.put("complete", complete);
+        }
+
+        if let Some(incomplete) = response.incomplete {
+            ben.put("incomplete", incomplete);
+        }
+
+        if response.failure_reason.is_none() {
+            let addrs = match response.peer_form {
+                PeerListForm::Compact => response.peers.iter().map(|p| p.get_address()).collect::<Option<Vec<_>>>(),
+                PeerListForm::Dictionary => None
+            };
+
+            match addrs {
+                Some(addrs) => {
+                    let (v4, v6) = split_peers(&addrs);
+                    ben.put("peers", v4);
+                    if !v6.is_empty() {
+                        ben.put("peers6", v6);
+                    }
+                }
+                None => {
+                    let mut list = BencodeArray::new();
+                    for peer in &response.peers {
+                        list.push(BencodeObject::from(peer));
+                    }
+                    ben.put("peers", list);
+                }
+            }
+        }
+
+        ben.sort_keys();
+        ben
+    }
+}
+
+fn read_peer_list(list: &BencodeArray, peers: &mut Vec<TrackerPeer>) -> io::Result<()> {
+    for peer in list.iter() {
+        let peer = peer.as_any().downcast_ref::<BencodeObject>()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Peer must be a dictionary"))?;
+        peers.push(TrackerPeer::try_from(peer)?);
+    }
+    Ok(())
+}
+
+impl FromBencode for AnnounceResponse {
+
+    fn from_bencode_with_offset(buf: &[u8]) -> io::Result<(Self, usize)> {
+        let (ben, off) = BencodeObject::from_bencode_with_offset(buf)?;
+        Ok((Self::try_from(&ben)?, off))
+    }
+}
+
+impl ToBencode for AnnounceResponse {
+
+    fn to_bencode(&self) -> Vec<u8> {
+        BencodeObject::from(self).to_bencode()
+    }
+}
@@ -0,0 +1,6 @@
+pub mod tracker_peer;
+pub mod announce_response;
+pub mod scrape_response;
+mod query_string;
+pub mod announce_request;
+pub mod scrape_request;
@@ -0,0 +1,32 @@
+use std::io;
+use crate::utils::url_encoding;
+
+pub fn split_url(url: &str) -> (&str, &str) {
+    let url = url.split('#').next().unwrap_or(url);
+    url.split_once('?').unwrap_or((url, ""))
+}
+
+pub fn join_url(base: &str, params: &[(&str, String)]) -> String {
+    let query: Vec<String> = params.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+    let separator = match base.contains('?') {
+        true if base.ends_with('?') || base.ends_with('&') => "",
+        true => "&",
+        false => "?"
+    };
+    format!("{}{}{}", base, separator, query.join("&"))
+}
+
+pub fn parse(query: &str) -> io::Result<Vec<(String, Vec<u8>)>> {
+    query.split('&').filter(|p| !p.is_empty()).map(|pair| {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let key = String::from_utf8(url_encoding::decode(key)?)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        Ok((key, url_encoding::decode(value)?))
+    }).collect()
+}
+
+pub fn parse_number<T: std::str::FromStr>(key: &str, value: &[u8]) -> io::Result<T> {
+    std::str::from_utf8(value).ok()
+        .and_then(|v| v.parse::<T>().ok())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid {} parameter", key)))
+}
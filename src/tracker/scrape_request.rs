@@ -0,0 +1,65 @@
+use std::io;
+use crate::metainfo::info_hash::InfoHash;
+use crate::tracker::query_string::{join_url, parse, split_url};
+use crate::utils::url_encoding;
+
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct ScrapeRequest {
+    info_hashes: Vec<InfoHash>
+}
+
+impl ScrapeRequest {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get_info_hashes(&self) -> &Vec<InfoHash> {
+        &self.info_hashes
+    }
+
+    pub fn add_info_hash(&mut self, info_hash: InfoHash) {
+        let info_hash = InfoHash::V1(info_hash.truncated());
+        if !self.info_hashes.contains(&info_hash) {
+            self.info_hashes.push(info_hash);
+        }
+    }
+
+    pub fn to_url(&self, scrape: &str) -> String {
+        let params: Vec<(&str, String)> = self.info_hashes.iter()
+            .map(|h| ("info_hash", url_encoding::encode(h.as_bytes())))
+            .collect();
+        join_url(scrape, &params)
+    }
+
+    pub fn from_url(url: &str) -> io::Result<Self> {
+        let (_, query) = split_url(url);
+        Self::from_query(query)
+    }
+
+    pub fn from_query(query: &str) -> io::Result<Self> {
+        let mut request = Self::new();
+
+        for (key, value) in parse(query)? {
+            if key == "info_hash" {
+                request.add_info_hash(InfoHash::V1(value.as_slice().try_into()
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "info_hash must be 20 bytes"))?));
+            }
+        }
+
+        Ok(request)
+    }
+}
+
+pub fn scrape_url(announce: &str) -> Option<String> {
+    let (base, query) = split_url(announce);
+    let slash = base.rfind('/')?;
+    let rest = base[slash + 1..].strip_prefix("announce")?;
+
+    let mut url = format!("{}scrape{}", &base[..=slash], rest);
+    if !query.is_empty() {
+        url.push('?');
+        url.push_str(query);
+    }
+    Some(url)
+}
@@ -0,0 +1,277 @@
+use std::io;
+use crate::metainfo::info_hash::InfoHash;
+use crate::variables::bencode_bytes::BencodeBytes;
+use crate::variables::bencode_number::BencodeNumber;
+use crate::variables::bencode_object::{BencodeObject, GetObject, ObjectOptions, PutObject};
+use crate::variables::inter::bencode_variable::{BencodeVariable, FromBencode, ToBencode};
+
+const FILE_KEYS: [&str; 4] = ["complete", "downloaded", "incomplete", "name"];
+const RESPONSE_KEYS: [&str; 3] = ["failure reason", "files", "flags"];
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScrapeFile {
+    complete: u64,
+    downloaded: u64,
+    incomplete: u64,
+    name: Option<Vec<u8>>,
+    extra: BencodeObject
+}
+
+impl ScrapeFile {
+
+    pub fn new(complete: u64, downloaded: u64, incomplete: u64) -> Self {
+        Self {
+            complete,
+            downloaded,
+            incomplete,
+            ..Self::default()
+        }
+    }
+
+    pub fn get_complete(&self) -> u64 {
+        self.complete
+    }
+
+    pub fn get_downloaded(&self) -> u64 {
+        self.downloaded
+    }
+
+    pub fn get_incomplete(&self) -> u64 {
+        self.incomplete
+    }
+
+    pub fn get_name(&self) -> Option<&str> {
+        self.name.as_ref().and_then(|n| std::str::from_utf8(n).ok())
+    }
+
+    pub fn get_name_bytes(&self) -> Option<&Vec<u8>> {
+        self.name.as_ref()
+    }
+
+    pub fn set_name(&mut self, name: &[u8]) {
+        self.name = Some(name.to_vec());
+    }
+
+    pub fn get_extra(&self) -> &BencodeObject {
+        &self.extra
+    }
+
+    pub fn get_extra_mut(&mut self) -> &mut BencodeObject {
+        &mut self.extra
+    }
+}
+
+impl TryFrom<&BencodeObject> for ScrapeFile {
+
+    type Error = io::Error;
+
+    fn try_from(ben: &BencodeObject) -> io::Result<Self> {
+        let number = |key: &str| -> io::Result<u64> {
+            ben.get::<BencodeNumber>(key)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("Scrape {} not found", key)))?
+                .parse::<u64>()
+        };
+
+        let name = ben.get::<BencodeBytes>("name").map(|n| n.as_bytes().to_vec());
+
+        let mut extra = BencodeObject::new();
+        for (key, value) in ben.iter() {
+            let known = match key.as_bytes() {
+                b"name" => name.is_some(),
+                k => FILE_KEYS.iter().any(|known| known.as_bytes() == k)
+            };
+
+            if !known {
+                extra.put(key.clone(), value.clone());
+            }
+        }
+
+        Ok(Self {
+            complete: number("complete")?,
+            downloaded: number("downloaded")?,
+            incomplete: number("incomplete")?,
+            name,
+            extra
+        })
+    }
+}
+
+impl From<&ScrapeFile> for BencodeObject {
+
+    fn from(file: &ScrapeFile) -> Self {
+        let mut ben = file.extra.clone();
+        ben.put("complete", file.complete);
+        ben.put("downloaded", file.downloaded);
+        ben.put("incomplete", file.incomplete);
+
+        if let Some(name) = &file.name {
+            ben.put("name", name.as_slice());
+        }
+
+        ben.sort_keys();
+        ben
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScrapeResponse {
+    files: Vec<([u8; 20], ScrapeFile)>,
+    failure_reason: Option<Vec<u8>>,
+    flags: Option<BencodeObject>,
+    extra: BencodeObject
+}
+
+impl ScrapeResponse {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn failure(reason: &str) -> Self {
+        Self {
+            failure_reason: Some(reason.as_bytes().to_vec()),
+            ..Self::default()
+        }
+    }
+
+    pub fn get_files(&self) -> &Vec<([u8; 20], ScrapeFile)> {
+        &self.files
+    }
+
+    pub fn get_file(&self, info_hash: &InfoHash) -> Option<&ScrapeFile> {
+        let key = info_hash.truncated();
+        self.files.iter().find(|(k, _)| *k == key).map(|(_, f)| f)
+    }
+
+    pub fn add_file(&mut self, info_hash: &InfoHash, file: ScrapeFile) {
+        let key = info_hash.truncated();
+        match self.files.iter().position(|(k, _)| *k == key) {
+            Some(i) => self.files[i].1 = file,
+            None => {
+                let i = self.files.partition_point(|(k, _)| *k < key);
+                self.files.insert(i, (key, file));
+            }
+        }
+    }
+
+    pub fn is_failure(&self) -> bool {
+        self.failure_reason.is_some()
+    }
+
+    pub fn get_failure_reason(&self) -> Option<&str> {
+        self.failure_reason.as_ref().and_then(|r| std::str::from_utf8(r).ok())
+    }
+
+    pub fn get_failure_reason_bytes(&self) -> Option<&Vec<u8>> {
+        self.failure_reason.as_ref()
+    }
+
+    pub fn get_min_request_interval(&self) -> Option<u32> {
+        self.flags.as_ref()
+            .and_then(|f| f.get::<BencodeNumber>("min_request_interval"))
+            .and_then(|n| n.parse::<u32>().ok())
+    }
+
+    pub fn set_min_request_interval(&mut self, interval: u32) {
+        self.flags.get_or_insert_with(BencodeObject::new).put("min_request_interval", interval);
+    }
+
+    pub fn get_extra(&self) -> &BencodeObject {
+        &self.extra
+    }
+
+    pub fn get_extra_mut(&mut self) -> &mut BencodeObject {
+        &mut self.extra
+    }
+}
+
+impl TryFrom<&BencodeObject> for ScrapeResponse {
+
+    type Error = io::Error;
+
+    fn try_from(ben: &BencodeObject) -> io::Result<Self> {
+        let mut files = Vec::new();
+
+        let list = ben.get::<BencodeObject>("files");
+        if let Some(list) = list {
+            for (key, file) in list.iter() {
+                let info_hash: [u8; 20] = key.as_bytes().try_into()
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Scrape key must be a 20 byte info hash"))?;
+
+                let file = file.as_any().downcast_ref::<BencodeObject>()
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Scrape file must be a dictionary"))?;
+
+                files.push((info_hash, ScrapeFile::try_from(file)?));
+            }
+        }
+
+        let flags = ben.get::<BencodeObject>("flags").cloned();
+        if let Some(interval) = flags.as_ref().and_then(|f| f.get::<BencodeNumber>("min_request_interval")) {
+            interval.parse::<u32>()?;
+        }
+
+        let failure_reason = ben.get::<BencodeBytes>("failure reason").map(|r| r.as_bytes().to_vec());
+
+        let mut extra = BencodeObject::new();
+        for (key, value) in ben.iter() {
+            let known = match key.as_bytes() {
+                b"files" => list.is_some(),
+                b"flags" => flags.is_some(),
+                b"failure reason" => failure_reason.is_some(),
+                k => RESPONSE_KEYS.iter().any(|known| known.as_bytes() == k)
+            };
+
+            if !known {
+                extra.put(key.clone(), value.clone());
+            }
+        }
+
+        Ok(Self {
+            files,
+            failure_reason,
+            flags,
+            extra
+        })
+    }
+}
+
+impl From<&ScrapeResponse> for BencodeObject {
+
+    fn from(response: &ScrapeResponse) -> Self {
+        let mut ben = response.extra.clone();
+
+        if let Some(reason) = &response.failure_reason {
+            ben.put("failure reason", reason.as_slice());
+        }
+
+        if !response.files.is_empty() || (response.failure_reason.is_none() && !ben.contains_key("files")) {
+            let mut files = BencodeObject::new();
+            for (info_hash, file) in &response.files {
+                files.put(BencodeBytes::from(*info_hash), BencodeObject::from(file));
+            }
+            ben.put("files", files);
+        }
+
+        if let Some(flags) = &response.flags {
+            ben.put("flags", flags.clone());
+        }
+
+        ben.sort_keys();
+        ben
+    }
+}
+
+impl FromBencode for ScrapeResponse {
+
+    fn from_bencode_with_offset(buf: &[u8]) -> io::Result<(Self, usize)> {
+        let (ben, off) = BencodeObject::from_bencode_with_offset(buf)?;
+        Ok((Self::try_from(&ben)?, off))
+    }
+}
+
+impl ToBencode for ScrapeResponse {
+
+    fn to_bencode(&self) -> Vec<u8> {
+        BencodeObject::from(self).to_bencode()
+    }
+}
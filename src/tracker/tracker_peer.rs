@@ -0,0 +1,89 @@
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use crate::variables::bencode_bytes::BencodeBytes;
+use crate::variables::bencode_number::BencodeNumber;
+use crate::variables::bencode_object::{BencodeObject, GetObject, PutObject};
+use crate::variables::inter::bencode_variable::BencodeVariable;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TrackerPeer {
+    host: String,
+    port: u16,
+    peer_id: Option<Vec<u8>>
+}
+
+impl TrackerPeer {
+
+    pub fn new(host: &str, port: u16) -> Self {
+        Self {
+            host: host.to_string(),
+            port,
+            peer_id: None
+        }
+    }
+
+    pub fn get_host(&self) -> &String {
+        &self.host
+    }
+
+    pub fn get_port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn get_peer_id(&self) -> Option<&Vec<u8>> {
+        self.peer_id.as_ref()
+    }
+
+    pub fn set_peer_id(&mut self, peer_id: &[u8]) {
+        self.peer_id = Some(peer_id.to_vec());
+    }
+
+    pub fn get_address(&self) -> Option<SocketAddr> {
+        self.host.parse::<IpAddr>().ok().map(|ip| SocketAddr::new(ip, self.port))
+    }
+}
+
+impl From<SocketAddr> for TrackerPeer {
+
+    fn from(addr: SocketAddr) -> Self {
+        Self::new(&addr.ip().to_string(), addr.port())
+    }
+}
+
+impl TryFrom<&BencodeObject> for TrackerPeer {
+
+    type Error = io::Error;
+
+    fn try_from(ben: &BencodeObject) -> io::Result<Self> {
+        let host = ben.get::<BencodeBytes>("ip")
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Peer ip not found"))?;
+        let host = String::from_utf8(host.as_bytes().to_vec())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        let port = ben.get::<BencodeNumber>("port")
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Peer port not found"))?
+            .parse::<u16>()?;
+
+        Ok(Self {
+            host,
+            port,
+            peer_id: ben.get::<BencodeBytes>("peer id").map(|p| p.as_bytes().to_vec())
+        })
+    }
+}
+
+impl From<&TrackerPeer> for BencodeObject {
+
+    fn from(peer: &TrackerPeer) -> Self {
+        let mut ben = BencodeObject::new();
+        ben.put("ip", peer.host.as_str());
+
+        if let Some(peer_id) = &peer.peer_id {
+            ben.put("peer id", peer_id.as_slice());
+        }
+
+        ben.put("port", peer.port);
+        ben.sort_keys();
+        ben
+    }
+}
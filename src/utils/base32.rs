@@ -0,0 +1,25 @@
+use std::io;
+
+pub fn decode(s: &str) -> io::Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(s.len() * 5 / 8);
+    let mut bits: u32 = 0;
+    let mut count = 0;
+
+    for c in s.trim_end_matches('=').bytes() {
+        let v = match c.to_ascii_uppercase() {
+            c @ b'A'..=b'Z' => c - b'A',
+            c @ b'2'..=b'7' => c - b'2' + 26,
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidInput, "Invalid base32 character"))
+        };
+
+        bits = (bits << 5) | v as u32;
+        count += 5;
+
+        if count >= 8 {
+            count -= 8;
+            out.push((bits >> count) as u8);
+        }
+    }
+
+    Ok(out)
+}
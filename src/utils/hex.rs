@@ -0,0 +1,20 @@
+use std::io;
+
+pub fn encode(buf: &[u8]) -> String {
+    buf.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub fn decode(s: &str) -> io::Result<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "Hex string has odd length"));
+    }
+
+    s.as_bytes().chunks(2).map(|c| {
+        let hi = (c[0] as char).to_digit(16);
+        let lo = (c[1] as char).to_digit(16);
+        match (hi, lo) {
+            (Some(hi), Some(lo)) => Ok((hi * 16 + lo) as u8),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidInput, "Invalid hex character"))
+        }
+    }).collect()
+}
@@ -1 +1,9 @@
 pub mod ordered_map;
+pub mod hex;
+pub mod sha1;
+pub mod sha256;
+pub mod parallel;
+pub mod random;
+pub mod base32;
+pub mod url_encoding;
+pub mod crc32c;
@@ -65,3 +65,10 @@ impl<K, V> OrderedMap<K, V> where K: Eq + Hash + Clone {
         self.map.is_empty()
     }
 }
+
+impl<K, V> OrderedMap<K, V> where K: Eq + Hash + Clone + Ord {
+
+    pub fn sort_keys(&mut self) {
+        self.keys.sort();
+    }
+}
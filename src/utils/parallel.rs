@@ -0,0 +1,59 @@
+use std::io;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+pub fn map<T, F>(jobs: usize, threads: usize, f: F) -> io::Result<Vec<T>>
+where
+    T: Send,
+    F: Fn(usize) -> io::Result<T> + Sync
+{
+    map_with(jobs, threads, || (), |_, i| f(i))
+}
+
+pub fn map_with<S, T, I, F>(jobs: usize, threads: usize, init: I, f: F) -> io::Result<Vec<T>>
+where
+    T: Send,
+    I: Fn() -> S + Sync,
+    F: Fn(&mut S, usize) -> io::Result<T> + Sync
+{
+    if threads <= 1 || jobs <= 1 {
+        let mut state = init();
+        return (0..jobs).map(|i| f(&mut state, i)).collect();
+    }
+
+    let next = AtomicUsize::new(0);
+    let failed = AtomicBool::new(false);
+    let results: Mutex<Vec<Option<T>>> = Mutex::new((0..jobs).map(|_| None).collect());
+    let error: Mutex<Option<io::Error>> = Mutex::new(None);
+
+    thread::scope(|s| {
+        for _ in 0..threads.min(jobs) {
+            s.spawn(|| {
+                let mut state = init();
+
+                loop {
+                    let i = next.fetch_add(1, Ordering::SeqCst);
+                    if i >= jobs || failed.load(Ordering::SeqCst) {
+                        break;
+                    }
+
+                    match f(&mut state, i) {
+                        Ok(v) => results.lock().unwrap()[i] = Some(v),
+                        Err(e) => {
+                            failed.store(true, Ordering::SeqCst);
+                            error.lock().unwrap().get_or_insert(e);
+                            break;
+                        }
+                    }
+                }
+            });
+        }
+    });
+
+    if let Some(e) = error.into_inner().unwrap() {
+        return Err(e);
+    }
+
+    Ok(results.into_inner().unwrap().into_iter().map(|v| v.unwrap()).collect())
+}
@@ -0,0 +1,44 @@
+use std::io;
+use crate::utils::hex;
+
+pub fn encode(buf: &[u8]) -> String {
+    let mut out = String::with_capacity(buf.len() * 3);
+    for &b in buf {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => out.push(b as char),
+            _ => out.push_str(&format!("%{:02X}", b))
+        }
+    }
+    out
+}
+
+pub fn decode(s: &str) -> io::Result<Vec<u8>> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                if i + 2 >= bytes.len() {
+                    return Err(io::Error::new(io::ErrorKind::InvalidInput, "Truncated percent encoding"));
+                }
+
+                let pair = std::str::from_utf8(&bytes[i + 1..i + 3])
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Invalid percent encoding"))?;
+                out.extend(hex::decode(pair)?);
+                i += 3;
+            }
+            b'+' => {
+                out.push(b' ');
+                i += 1;
+            }
+            b => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+
+    Ok(out)
+}
@@ -41,7 +41,7 @@ impl BencodeArray {
             .downcast_mut::<V>()
     }
 
-    fn remove(&mut self, index: usize) -> Box<dyn BencodeVariable> {
+    pub fn remove(&mut self, index: usize) -> Box<dyn BencodeVariable> {
         self.value.remove(index)
     }
 
@@ -49,9 +49,24 @@ impl BencodeArray {
         self.value.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.value.is_empty()
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = &Box<dyn BencodeVariable>> {
         self.value.iter()
     }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Box<dyn BencodeVariable>> {
+        self.value.iter_mut()
+    }
+}
+
+impl Default for BencodeArray {
+
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl BencodeVariable for BencodeArray {
@@ -92,15 +107,15 @@ impl ToBencode for BencodeArray {
 impl FromBencode for BencodeArray {
 
     fn from_bencode_with_offset(buf: &[u8]) -> io::Result<(Self, usize)> {
-        if !BencodeTypes::from_code(buf[0]).eq(&BencodeTypes::Array) {
+        if !BencodeTypes::try_from_code(*buf.first().ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "Empty buffer"))?)?.eq(&BencodeTypes::Array) {
             return Err(io::Error::new(io::ErrorKind::InvalidInput, "Invalid prefix for object"));
         }
 
         let mut value = Vec::new();
 
         let mut off = 1;
-        while buf[off] != BencodeTypes::Array.suffix() {
-            let (v, l) = match BencodeTypes::from_code(buf[off]) {
+        while *buf.get(off).ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "Truncated array"))? != BencodeTypes::Array.suffix() {
+            let (v, l) = match BencodeTypes::try_from_code(*buf.get(off).ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "Truncated array"))?)? {
                 BencodeTypes::Array => {
                     let (v, l) = BencodeArray::from_bencode_with_offset(&buf[off..])?;
                     (v.upcast(), l)
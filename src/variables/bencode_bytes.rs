@@ -2,10 +2,11 @@ use std::any::Any;
 use std::{fmt, io};
 use std::fmt::Formatter;
 use std::str::from_utf8;
+use crate::utils::hex;
 use crate::variables::inter::bencode_types::BencodeTypes;
 use crate::variables::inter::bencode_variable::{BencodeCast, BencodeVariable, FromBencode, ToBencode};
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
 pub struct BencodeBytes {
     value: Vec<u8>
 }
@@ -19,6 +20,10 @@ impl BencodeBytes {
     pub fn as_str(&self) -> &str {
         from_utf8(&self.value).unwrap()
     }
+
+    pub fn to_str(&self) -> Option<&str> {
+        from_utf8(&self.value).ok()
+    }
 }
 
 impl BencodeVariable for BencodeBytes {
@@ -180,7 +185,7 @@ impl BencodeCast<BencodeBytes> for Vec<u8> {
 impl BencodeCast<BencodeBytes> for String {
 
     fn cast(value: &BencodeBytes) -> io::Result<Self> {
-        Ok(String::from_utf8(value.value.clone()).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?)
+        String::from_utf8(value.value.clone()).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
     }
 }
 
@@ -198,16 +203,26 @@ impl ToBencode for BencodeBytes {
 impl FromBencode for BencodeBytes {
 
     fn from_bencode_with_offset(buf: &[u8]) -> io::Result<(Self, usize)> {
-        if !BencodeTypes::from_code(buf[0]).eq(&BencodeTypes::Bytes) {
+        if !BencodeTypes::try_from_code(*buf.first().ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "Empty buffer"))?)?.eq(&BencodeTypes::Bytes) {
             return Err(io::Error::new(io::ErrorKind::InvalidInput, "Invalid prefix for bytes"));
         }
 
         let mut off = 0;
-        while buf[off] != BencodeTypes::Bytes.delimiter() {
+        let mut length: usize = 0;
+        while buf.get(off).ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "Truncated bytes length"))? != &BencodeTypes::Bytes.delimiter() {
+            if !buf[off].is_ascii_digit() {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid bytes length"));
+            }
+
+            length = length.checked_mul(10)
+                .and_then(|l| l.checked_add((buf[off] - b'0') as usize))
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Bytes length overflow"))?;
             off += 1;
         }
 
-        let length = buf.iter().take(off).fold(0, |acc, &b| acc * 10 + (b - b'0') as usize);
+        if buf.len() - off - 1 < length {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Truncated bytes"));
+        }
 
         Ok((Self {
             value: buf[off + 1..off + 1 + length].to_vec()
@@ -218,6 +233,9 @@ impl FromBencode for BencodeBytes {
 impl fmt::Display for BencodeBytes {
 
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", String::from_utf8(self.value.clone()).unwrap())
+        match from_utf8(&self.value) {
+            Ok(s) => write!(f, "{}", s),
+            Err(_) => write!(f, "0x{}", hex::encode(&self.value))
+        }
     }
 }
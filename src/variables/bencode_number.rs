@@ -47,7 +47,10 @@ macro_rules! impl_bencode_number {
             impl BencodeCast<BencodeNumber> for $type {
 
                 fn cast(value: &BencodeNumber) -> io::Result<Self> {
-                    Ok(String::from_utf8(value.value.clone()).unwrap().parse::<$type>().unwrap())
+                    String::from_utf8(value.value.clone())
+                        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+                        .parse::<$type>()
+                        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
                 }
             }
 
@@ -79,12 +82,12 @@ impl ToBencode for BencodeNumber {
 impl FromBencode for BencodeNumber {
 
     fn from_bencode_with_offset(buf: &[u8]) -> io::Result<(Self, usize)> {
-        if !BencodeTypes::from_code(buf[0]).eq(&BencodeTypes::Number) {
+        if !BencodeTypes::try_from_code(*buf.first().ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "Empty buffer"))?)?.eq(&BencodeTypes::Number) {
             return Err(io::Error::new(io::ErrorKind::InvalidInput, "Invalid prefix for number"));
         }
 
         let mut off = 1;
-        while buf[off] != BencodeTypes::Number.suffix() {
+        while *buf.get(off).ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "Truncated number"))? != BencodeTypes::Number.suffix() {
             off += 1;
         }
 
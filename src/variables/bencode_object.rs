@@ -44,6 +44,28 @@ impl BencodeObject {
         self.value.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.value.is_empty()
+    }
+
+    pub fn sort_keys(&mut self) {
+        self.value.sort_keys();
+
+        for key in self.value.keys().clone() {
+            if let Some(v) = self.value.get_mut(&key) {
+                sort_nested(v);
+            }
+        }
+    }
+
+    pub fn sort_top_level_keys(&mut self) {
+        self.value.sort_keys();
+    }
+
+    pub fn get_variable(&self, key: &str) -> Option<&dyn BencodeVariable> {
+        self.value.get(&BencodeBytes::from(key)).map(|v| v.as_ref())
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = (&BencodeBytes, &Box<dyn BencodeVariable>)> {
         self.value.keys().iter().filter_map(move |key| {
             let value = self.value.get(key)?;
@@ -52,6 +74,23 @@ impl BencodeObject {
     }
 }
 
+impl Default for BencodeObject {
+
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn sort_nested(value: &mut Box<dyn BencodeVariable>) {
+    if let Some(o) = value.as_any_mut().downcast_mut::<BencodeObject>() {
+        o.sort_keys();
+
+    } else if let Some(a) = value.as_any_mut().downcast_mut::<BencodeArray>() {
+        for v in a.iter_mut() {
+            sort_nested(v);
+        }
+    }
+}
 
 impl BencodeVariable for BencodeObject {
 
@@ -92,18 +131,18 @@ impl ToBencode for BencodeObject {
 impl FromBencode for BencodeObject {
 
     fn from_bencode_with_offset(buf: &[u8]) -> io::Result<(Self, usize)> {
-        if !BencodeTypes::from_code(buf[0]).eq(&BencodeTypes::Object) {
+        if !BencodeTypes::try_from_code(*buf.first().ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "Empty buffer"))?)?.eq(&BencodeTypes::Object) {
             return Err(io::Error::new(io::ErrorKind::InvalidInput, "Invalid prefix for object"));
         }
 
         let mut value = OrderedMap::new();
 
         let mut off = 1;
-        while buf[off] != BencodeTypes::Object.suffix() {
+        while *buf.get(off).ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "Truncated object"))? != BencodeTypes::Object.suffix() {
             let (k, l) = BencodeBytes::from_bencode_with_offset(&buf[off..])?;
             off += l;
 
-            let (v, l) = match BencodeTypes::from_code(buf[off]) {
+            let (v, l) = match BencodeTypes::try_from_code(*buf.get(off).ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "Truncated object"))?)? {
                 BencodeTypes::Array => {
                     let (v, l) = BencodeArray::from_bencode_with_offset(&buf[off..])?;
                     (v.upcast(), l)
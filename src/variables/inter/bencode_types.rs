@@ -1,3 +1,5 @@
+use std::io;
+
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub enum BencodeTypes {
     Object,
@@ -18,6 +20,16 @@ impl BencodeTypes {
         }
     }
 
+    pub fn try_from_code(c: u8) -> io::Result<Self> {
+        match c {
+            b'l' => Ok(Self::Array),
+            b'd' => Ok(Self::Object),
+            b'i' => Ok(Self::Number),
+            b'0'..=b'9' => Ok(Self::Bytes),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid bencode type prefix"))
+        }
+    }
+
     pub fn prefix(&self) -> u8 {
         match self {
             Self::Array => b'l',
@@ -1,6 +1,10 @@
 use std::any::Any;
 use std::fmt::{Debug, Display};
 use std::io;
+use crate::variables::bencode_array::BencodeArray;
+use crate::variables::bencode_bytes::BencodeBytes;
+use crate::variables::bencode_number::BencodeNumber;
+use crate::variables::bencode_object::BencodeObject;
 use crate::variables::inter::bencode_types::BencodeTypes;
 
 pub trait BencodeVariable: Display + Debug + ToBencode + FromBencode {
@@ -50,3 +54,29 @@ pub trait FromBencode {
 
     fn from_bencode_with_offset(buf: &[u8]) -> io::Result<(Self, usize)> where Self: Sized;
 }
+
+impl FromBencode for Box<dyn BencodeVariable> {
+
+    fn from_bencode_with_offset(buf: &[u8]) -> io::Result<(Self, usize)> {
+        let code = *buf.first().ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "Empty buffer"))?;
+
+        Ok(match BencodeTypes::try_from_code(code)? {
+            BencodeTypes::Array => {
+                let (v, l) = BencodeArray::from_bencode_with_offset(buf)?;
+                (v.upcast(), l)
+            }
+            BencodeTypes::Object => {
+                let (v, l) = BencodeObject::from_bencode_with_offset(buf)?;
+                (v.upcast(), l)
+            }
+            BencodeTypes::Number => {
+                let (v, l) = BencodeNumber::from_bencode_with_offset(buf)?;
+                (v.upcast(), l)
+            }
+            BencodeTypes::Bytes => {
+                let (v, l) = BencodeBytes::from_bencode_with_offset(buf)?;
+                (v.upcast(), l)
+            }
+        })
+    }
+}